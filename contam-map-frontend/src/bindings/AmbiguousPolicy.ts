@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What to do with contigs marked `Ambiguous` when exporting refined bins.
+ * Contigs marked `Contamination` are always left out.
+ */
+export type AmbiguousPolicy = "Keep" | "Drop";
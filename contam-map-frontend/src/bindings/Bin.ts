@@ -2,5 +2,26 @@
 import type { BinId } from "./BinId";
 import type { BinQuality } from "./BinQuality";
 import type { ContigAssignment } from "./ContigAssignment";
+import type { RnaCounts } from "./RnaCounts";
 
-export type Bin = { id: BinId, contig_metadata: Array<ContigAssignment>, completeness: number | null, contamination: number | null, quality: BinQuality | null, };
+export type Bin = { id: BinId, contig_metadata: Array<ContigAssignment>, completeness: number | null, contamination: number | null, quality: BinQuality | null, 
+/**
+ * Lineage from a bin classifier such as GTDB-Tk.
+ */
+taxonomy: string | null, 
+/**
+ * rRNA and tRNA counts, if the project has them.
+ */
+rna: RnaCounts | null, 
+/**
+ * Fraction of GC bases.
+ */
+gc_content: number | null, genome_size: number | null, n50: number | null, contig_count: number | null, 
+/**
+ * Fraction of the genome in predicted coding sequences.
+ */
+coding_density: number | null, 
+/**
+ * Free text from the quality tool, e.g. CheckM2 `Additional_Notes`.
+ */
+quality_notes: string | null, };
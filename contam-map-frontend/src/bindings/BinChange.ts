@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinId } from "./BinId";
+import type { ContigChange } from "./ContigChange";
+
+/**
+ * Contigs of a bin that differ before and after an event. Contigs that are
+ * left as they were, in the same order, are not recorded.
+ */
+export type BinChange = { bin: BinId, 
+/**
+ * Whether the bin existed before and after the event.
+ */
+existed_before: boolean, exists_after: boolean, contigs: Array<ContigChange>, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinId } from "./BinId";
+import type { ContigId } from "./ContigId";
+
+export type BinFastaExport = { bin_id: BinId, path: string | null, n_contigs: number, missing_contigs: Array<ContigId>, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinQuality } from "./BinQuality";
+
+/**
+ * Bounds on bin attributes. Bins missing an attribute that is bounded are
+ * left out.
+ */
+export type BinFilter = { 
+/**
+ * Quality tiers to keep. Any tier if empty.
+ */
+qualities: Array<BinQuality>, min_completeness: number | null, max_contamination: number | null, min_gc_content: number | null, max_gc_content: number | null, min_genome_size: number | null, min_n50: number | null, };
@@ -1,3 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type BinQuality = "HQ" | "MQ" | "LQ";
+/**
+ * MIMAG quality tier. `NC` (near-complete) bins meet the high quality
+ * completeness and contamination thresholds but not the rRNA and tRNA
+ * criteria, or are missing from the project's rRNA and tRNA counts.
+ */
+export type BinQuality = "HQ" | "NC" | "MQ" | "LQ";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Bin attribute to sort bins by.
+ */
+export type BinSortKey = "Id" | "Completeness" | "Contamination" | "GcContent" | "GenomeSize" | "N50" | "ContigCount" | "CodingDensity";
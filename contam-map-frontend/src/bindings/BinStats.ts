@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Genome statistics reported by the quality tool, as far as it reports them.
+ */
+export type BinStats = { 
+/**
+ * Fraction of GC bases.
+ */
+gc_content: number | null, genome_size: number | null, n50: number | null, contig_count: number | null, 
+/**
+ * Fraction of the genome in predicted coding sequences.
+ */
+coding_density: number | null, 
+/**
+ * Free text from the quality tool, e.g. CheckM2 `Additional_Notes`.
+ */
+quality_notes: string | null, };
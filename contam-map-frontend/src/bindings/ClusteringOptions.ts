@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DistanceMetric } from "./DistanceMetric";
+import type { Linkage } from "./Linkage";
+
+export type ClusteringOptions = { linkage: Linkage, metric: DistanceMetric, };
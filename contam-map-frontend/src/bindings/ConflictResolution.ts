@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinId } from "./BinId";
+
+/**
+ * Keep a contig only in `keep`, removing it from every other bin.
+ */
+export type ConflictResolution = { keep: BinId, user?: string, };
@@ -2,4 +2,12 @@
 import type { Assignment } from "./Assignment";
 import type { ContigId } from "./ContigId";
 
-export type ContigAssignment = { contig_id: ContigId, assignment: Assignment, };
+export type ContigAssignment = { contig_id: ContigId, assignment: Assignment, 
+/**
+ * Free text explaining the assignment.
+ */
+note?: string, 
+/**
+ * Tags can't contain ',' since they are saved comma separated.
+ */
+tags: Array<string>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AmbiguousPolicy } from "./AmbiguousPolicy";
+
+export type ContigBinExportRequest = { ambiguous: AmbiguousPolicy, 
+/**
+ * Relative to the project output directory, which it can't leave.
+ */
+output_path: string | null, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContigId } from "./ContigId";
+import type { PlacedContig } from "./PlacedContig";
+
+/**
+ * A contig that was added, removed or edited. `None` means the contig was
+ * not in the bin.
+ */
+export type ContigChange = { contig_id: ContigId, before: PlacedContig | null, after: PlacedContig | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinId } from "./BinId";
+import type { ContigId } from "./ContigId";
+
+/**
+ * Contig that is in more than one bin.
+ */
+export type ContigConflict = { contig_id: ContigId, bins: Array<BinId>, };
@@ -1,4 +1,19 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Assignment } from "./Assignment";
+import type { SequenceStats } from "./SequenceStats";
 
-export type ContigMetadata = { contig_id: string, assignment: Assignment, mean_coverage: number, note: string | null, };
+export type ContigMetadata = { contig_id: string, assignment: Assignment, mean_coverage: number, note: string | null, tags: Array<string>, 
+/**
+ * Other bins the contig is also assigned to.
+ */
+other_bins: Array<string>, sequence: SequenceStats | null, sample_depths: Array<number> | null, 
+/**
+ * Log2 deviation of the depth profile from the rest of the bin. Only
+ * set when a bin is selected.
+ */
+coverage_deviation: number | null, taxonomy: string | null, 
+/**
+ * Whether `taxonomy` matches the taxonomy of the bin. Only set when a
+ * bin is selected and both are labelled.
+ */
+taxonomy_agrees: boolean | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinId } from "./BinId";
+
+/**
+ * Add an unbinned contig to `bin`.
+ */
+export type ContigRecruit = { bin: BinId, user?: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Assignment } from "./Assignment";
+import type { ContigId } from "./ContigId";
+
+export type ContigScore = { contig_id: ContigId, score: number | null, n_motifs: number, current: Assignment, suggestion: Assignment, 
+/**
+ * Deviation of the contig's depth profile from the bin, see
+ * `CoverageProfile::deviation`.
+ */
+coverage_deviation: number | null, coverage_outlier: boolean, };
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type ContigSelection = { "Bin": string } | { "Contigs": Array<string> };
+export type ContigSelection = { "Bin": string } | { "Contigs": Array<string> } | "Unbinned";
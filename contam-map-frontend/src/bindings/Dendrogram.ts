@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DendrogramNode } from "./DendrogramNode";
+
+export type Dendrogram = { n_leaves: number, merges: Array<DendrogramNode>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One merge step of the clustering. Leaves are numbered `0..n` and the
+ * cluster created by merge `i` is numbered `n + i`, as in scipy's linkage
+ * matrix.
+ */
+export type DendrogramNode = { left: number, right: number, distance: number, size: number, };
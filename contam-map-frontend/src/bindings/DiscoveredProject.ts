@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputFileStatus } from "./InputFileStatus";
+import type { ProjectDetails } from "./ProjectDetails";
+
+/**
+ * A `project.toml` found on disk. `details` is `None` and `error` is set if
+ * the file could not be parsed.
+ */
+export type DiscoveredProject = { path: string, details: ProjectDetails | null, error: string | null, inputs: Array<InputFileStatus>, };
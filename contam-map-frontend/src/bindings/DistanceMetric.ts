@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Distance between two observations. Cells that are `None` in either
+ * observation are skipped and the remaining distance is scaled up to the full
+ * number of dimensions. Pairs without any shared cells, or with fewer than
+ * two for `Correlation`, get the largest distance seen in the matrix.
+ */
+export type DistanceMetric = "Euclidean" | "Manhattan" | "Correlation";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Contig listed more than once in the contig_bin file. Fails the preflight
+ * if it is in different bins and the project uses `DuplicatePolicy::Error`.
+ */
+export type DuplicateAssignment = { contig: string, bins: Array<string>, };
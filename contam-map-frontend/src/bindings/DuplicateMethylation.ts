@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Contig with more than one methylation row for the same motif. Creating
+ * the project fails on these.
+ */
+export type DuplicateMethylation = { contig: string, motif: string, mod_type: string, mod_position: number, };
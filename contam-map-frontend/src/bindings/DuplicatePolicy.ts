@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What to do when the contig_bin file assigns a contig to more than one bin.
+ * Rows repeating the same contig and bin are always merged.
+ */
+export type DuplicatePolicy = "Error" | "KeepFirst" | "KeepAll";
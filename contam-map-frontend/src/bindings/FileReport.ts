@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Sample } from "./Sample";
+
+export type FileReport = { 
+/**
+ * `ProjectDetails` field the file came from.
+ */
+name: string, path: string, exists: boolean, rows: number, missing_columns: Array<string>, extra_columns: Array<string>, 
+/**
+ * Rows that could not be parsed.
+ */
+errors: Sample<string>, };
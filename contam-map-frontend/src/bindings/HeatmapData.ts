@@ -1,4 +1,13 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { ContigMetadata } from "./ContigMetadata";
+import type { Dendrogram } from "./Dendrogram";
 
-export type HeatmapData = { contigs: Array<string>, motifs: Array<string>, matrix: Array<Array<number | null>>, metadata: { [key in string]?: ContigMetadata } | null, };
+export type HeatmapData = { contigs: Array<string>, motifs: Array<string>, matrix: Array<Array<number | null>>, metadata: { [key in string]?: ContigMetadata } | null, 
+/**
+ * Samples of `ContigMetadata::sample_depths`.
+ */
+depth_samples: Array<string>, 
+/**
+ * Taxonomy of the selected bin.
+ */
+bin_taxonomy: string | null, contig_dendrogram: Dendrogram | null, motif_dendrogram: Dendrogram | null, };
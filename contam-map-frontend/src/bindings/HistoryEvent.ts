@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinChange } from "./BinChange";
+import type { HistoryAction } from "./HistoryAction";
+
+/**
+ * A recorded curation change. Undone events stay in the history until a new
+ * change is recorded, at which point they can no longer be redone and are
+ * dropped.
+ */
+export type HistoryEvent = { id: bigint, 
+/**
+ * Milliseconds since the unix epoch.
+ */
+timestamp: number, user: string | null, action: HistoryAction, description: string, changes: Array<BinChange>, undone: boolean, };
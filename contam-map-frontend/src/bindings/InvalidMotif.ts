@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Motif that `Motif::new` rejects.
+ */
+export type InvalidMotif = { motif: string, mod_type: string, mod_position: number, error: string, rows: number, };
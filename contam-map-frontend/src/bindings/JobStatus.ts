@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JobKind } from "./JobKind";
+import type { JobState } from "./JobState";
+import type { LoadProgressSnapshot } from "./LoadProgressSnapshot";
+
+export type JobStatus = { job_id: number, kind: JobKind, project_id: string | null, state: JobState, progress: LoadProgressSnapshot, error: string | null, };
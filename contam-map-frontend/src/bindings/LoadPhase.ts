@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Step of project creation or loading currently running.
+ */
+export type LoadPhase = "Pending" | "ContigBin" | "Quality" | "Methylation" | "Assembly" | "Coverage" | "Taxonomy" | "Saving" | "Done";
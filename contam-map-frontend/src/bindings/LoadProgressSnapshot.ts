@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LoadPhase } from "./LoadPhase";
+
+export type LoadProgressSnapshot = { phase: LoadPhase, contig_bin_rows: number, quality_rows: number, methylation_rows: number, methylation_bytes: number, };
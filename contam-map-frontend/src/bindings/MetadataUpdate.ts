@@ -2,4 +2,4 @@
 import type { BinId } from "./BinId";
 import type { ContigAssignment } from "./ContigAssignment";
 
-export type MetadataUpdate = { bin: BinId, contigs: Array<ContigAssignment>, };
+export type MetadataUpdate = { bin: BinId, contigs: Array<ContigAssignment>, user?: string, };
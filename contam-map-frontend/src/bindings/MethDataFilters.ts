@@ -1,4 +1,9 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ClusteringOptions } from "./ClusteringOptions";
 import type { ContigSelection } from "./ContigSelection";
 
-export type MethDataFilters = { selection: ContigSelection, min_n_motif_obs: number | null, min_motif_variance: number | null, min_coverage: number | null, min_methylation_value: number | null, };
+export type MethDataFilters = { selection: ContigSelection, min_n_motif_obs: number | null, min_motif_variance: number | null, min_coverage: number | null, min_methylation_value: number | null, 
+/**
+ * Sequence filters, only allowed if the project has an assembly.
+ */
+min_length: number | null, min_gc_content: number | null, max_gc_content: number | null, clustering: ClusteringOptions | null, };
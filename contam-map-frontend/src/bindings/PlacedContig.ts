@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContigAssignment } from "./ContigAssignment";
+
+/**
+ * A contig at its position in a bin.
+ */
+export type PlacedContig = { position: number, contig: ContigAssignment, };
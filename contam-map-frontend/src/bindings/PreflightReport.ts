@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicateAssignment } from "./DuplicateAssignment";
+import type { DuplicateMethylation } from "./DuplicateMethylation";
+import type { FileReport } from "./FileReport";
+import type { InvalidMotif } from "./InvalidMotif";
+import type { Sample } from "./Sample";
+
+/**
+ * Result of checking the inputs of a project before creating it. `ok` is
+ * false if an input is missing or `Project::new` would fail on it. The
+ * remaining findings are warnings.
+ */
+export type PreflightReport = { ok: boolean, files: Array<FileReport>, invalid_motifs: Array<InvalidMotif>, duplicate_methylation: Sample<DuplicateMethylation>, contigs_without_methylation: Sample<string>, methylation_without_bin: Sample<string>, bins_without_quality: Sample<string>, duplicate_assignments: Sample<DuplicateAssignment>, };
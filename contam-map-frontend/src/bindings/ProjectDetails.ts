@@ -1,3 +1,19 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicatePolicy } from "./DuplicatePolicy";
+import type { QualityFormat } from "./QualityFormat";
+import type { QualityProfile } from "./QualityProfile";
+import type { TaxonomySource } from "./TaxonomySource";
 
-export type ProjectDetails = { project_id: string, methylation_data_path: string, contig_bin_path: string, bin_quality_path: string | null, output_path: string, };
+export type ProjectDetails = { project_id: string, methylation_data_path: string, contig_bin_path: string, bin_quality_path: string | null, quality_format: QualityFormat, 
+/**
+ * Per-bin rRNA and tRNA counts for the MIMAG high quality criteria.
+ */
+rna_path?: string, quality_profile: QualityProfile, assembly_path: string | null, 
+/**
+ * Per-sample depth table from MetaBAT2 or CoverM.
+ */
+depth_path?: string, 
+/**
+ * Bin and contig classifier outputs.
+ */
+taxonomy: Array<TaxonomySource>, output_path: string, duplicate_policy: DuplicatePolicy, };
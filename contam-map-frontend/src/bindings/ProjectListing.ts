@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { InputFileStatus } from "./InputFileStatus";
+import type { ProjectDetails } from "./ProjectDetails";
+
+/**
+ * A project found under the projects directory.
+ */
+export type ProjectListing = { 
+/**
+ * The project's output directory belongs to a loaded project.
+ */
+loaded: boolean, path: string, details: ProjectDetails | null, error: string | null, inputs: Array<InputFileStatus>, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Overview of a loaded project.
+ */
+export type ProjectSummary = { project_id: string, outdir: string, bins: number, contigs: number, motifs: number, 
+/**
+ * Contigs in more than one bin.
+ */
+conflicts: number, 
+/**
+ * Contigs with methylation data that are not in any bin.
+ */
+unbinned: number, 
+/**
+ * Rough estimate of the memory held by the project.
+ */
+memory_bytes: number, modified_at: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Column names of a user-mapped quality table.
+ */
+export type QualityColumns = { bin: string, completeness: string, contamination: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QualityColumns } from "./QualityColumns";
+
+/**
+ * Layout of a bin quality table. `Auto` detects the format from the header.
+ */
+export type QualityFormat = "Auto" | "Checkm2" | "Checkm1" | "Busco" | { "Mapped": QualityColumns };
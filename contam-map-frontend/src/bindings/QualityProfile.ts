@@ -0,0 +1,21 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { QualityTier } from "./QualityTier";
+
+/**
+ * Thresholds used to assign `BinQuality`. The default follows MIMAG: more
+ * than 90% complete and less than 5% contaminated for high quality, at least
+ * 50% complete and less than 10% contaminated for medium quality.
+ *
+ * Bins at exactly 10% contamination used to be medium quality and bins at
+ * exactly 50% completeness low quality. Projects are reassessed when they are
+ * loaded, so both move to the MIMAG tier.
+ */
+export type QualityProfile = { high: QualityTier, medium: QualityTier, 
+/**
+ * Whether high quality bins need a 5S, a 16S and a 23S rRNA.
+ */
+require_rrnas: boolean, 
+/**
+ * Amino acids with a tRNA that high quality bins need, out of 20.
+ */
+min_trnas: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Completeness and contamination bounds of a tier. Contamination must be
+ * below `max_contamination`. Completeness must be above `min_completeness`,
+ * or equal to it if `completeness_inclusive` is set.
+ */
+export type QualityTier = { min_completeness: number, completeness_inclusive: boolean, max_contamination: number, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { BinCandidate } from "./BinCandidate";
+import type { ContigId } from "./ContigId";
+
+/**
+ * Closest bin for an unbinned contig. `recruit` is set when the contig is
+ * as close to the bin as a clean member would be.
+ */
+export type Recruitment = { contig_id: ContigId, best: BinCandidate, runner_up: BinCandidate | null, recruit: boolean, };
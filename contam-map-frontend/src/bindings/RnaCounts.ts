@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Number of rRNA genes of each type and of amino acids with a tRNA.
+ */
+export type RnaCounts = { rrna_5s: number, rrna_16s: number, rrna_23s: number, trnas: number, };
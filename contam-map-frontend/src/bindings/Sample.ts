@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Count of a finding with the first few occurrences.
+ */
+export type Sample<T> = { total: number, examples: Array<T>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ScoringOptions = { min_n_motif_obs: number, min_shared_motifs: number, ambiguous_threshold: number, contamination_threshold: number, 
+/**
+ * Coverage deviation, in log2 fold change, from which a contig is a
+ * coverage outlier in its bin.
+ */
+coverage_threshold: number, };
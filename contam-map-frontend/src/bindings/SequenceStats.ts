@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Attributes of a contig sequence from the assembly.
+ */
+export type SequenceStats = { length: number, 
+/**
+ * Fraction of G and C among the called bases, as in CheckM2.
+ */
+gc_content: number, n_count: number, };
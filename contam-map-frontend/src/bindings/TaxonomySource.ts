@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TaxonomyFormat } from "./TaxonomyFormat";
+
+/**
+ * A classifier output to read labels from.
+ */
+export type TaxonomySource = { format: TaxonomyFormat, path: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ContigId } from "./ContigId";
+
+/**
+ * Contig with methylation data that is not in any bin.
+ */
+export type UnbinnedContig = { contig_id: ContigId, mean_coverage: number, n_motifs: number, };
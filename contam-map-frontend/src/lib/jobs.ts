@@ -0,0 +1,50 @@
+import type { ErrorResponse } from "../bindings/ErrorResponse";
+import type { JobStatus } from "../bindings/JobStatus";
+
+const POLL_INTERVAL_MS = 500;
+
+// Start a project creation or load job and return its initial status.
+export async function startJob(url: string, body: unknown): Promise<JobStatus> {
+	const response = await fetch(url, {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify(body)
+	});
+
+	if (!response.ok) {
+		const error = await response.json() as ErrorResponse;
+		throw new Error(error.message);
+	}
+
+	return await response.json() as JobStatus;
+}
+
+// Poll a job until it stops running, reporting every status on the way.
+export async function waitForJob(jobId: number, onUpdate: (job: JobStatus) => void): Promise<JobStatus> {
+	while (true) {
+		const response = await fetch(`/api/jobs/${jobId}`);
+		if (!response.ok) {
+			const error = await response.json() as ErrorResponse;
+			throw new Error(error.message);
+		}
+
+		const job = await response.json() as JobStatus;
+		onUpdate(job);
+
+		switch (job.state) {
+			case "Running":
+				await new Promise((resolve) => setTimeout(resolve, POLL_INTERVAL_MS));
+				break;
+			case "Completed":
+				return job;
+			case "Cancelled":
+				throw new Error("Cancelled");
+			case "Failed":
+				throw new Error(job.error ?? "Job failed");
+		}
+	}
+}
+
+export async function cancelJob(jobId: number): Promise<void> {
+	await fetch(`/api/jobs/${jobId}/cancel`, { method: 'POST' });
+}
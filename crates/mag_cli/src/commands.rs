@@ -0,0 +1,250 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::{Context, bail};
+use mag_core::{
+    analysis::contamination::ScoringOptions,
+    bin::{Bin, BinId},
+    contig::{Assignment, ContigId},
+    export::AmbiguousPolicy,
+    io::reader::assignment::AssignmentReader,
+    project::Project,
+};
+use serde::Serialize;
+
+use crate::ScoringArgs;
+
+fn load(project: PathBuf) -> anyhow::Result<Project> {
+    Project::load_from_path(project.clone())
+        .with_context(|| format!("Could not load project '{}'", project.display()))
+}
+
+fn tsv_writer() -> csv::Writer<std::io::Stdout> {
+    csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(true)
+        .from_writer(std::io::stdout())
+}
+
+#[derive(Serialize)]
+struct BinSummaryRow<'a> {
+    bin: &'a str,
+    n_contigs: usize,
+    n_clean: usize,
+    n_contamination: usize,
+    n_ambiguous: usize,
+    n_unassigned: usize,
+    completeness: Option<f64>,
+    contamination: Option<f64>,
+    quality: Option<String>,
+    gc_content: Option<f64>,
+    genome_size: Option<u64>,
+    n50: Option<u64>,
+    contig_count: Option<u64>,
+    coding_density: Option<f64>,
+}
+
+impl<'a> BinSummaryRow<'a> {
+    fn from_bin(bin: &'a Bin) -> Self {
+        let count = |a: Assignment| {
+            bin.contig_metadata
+                .iter()
+                .filter(|c| c.assignment == a)
+                .count()
+        };
+
+        Self {
+            bin: &bin.id.0,
+            n_contigs: bin.contig_metadata.len(),
+            n_clean: count(Assignment::Clean),
+            n_contamination: count(Assignment::Contamination),
+            n_ambiguous: count(Assignment::Ambiguous),
+            n_unassigned: count(Assignment::None),
+            completeness: bin.completeness,
+            contamination: bin.contamination,
+            quality: bin.quality.as_ref().map(|q| q.to_string()),
+            gc_content: bin.stats.gc_content,
+            genome_size: bin.stats.genome_size,
+            n50: bin.stats.n50,
+            contig_count: bin.stats.contig_count,
+            coding_density: bin.stats.coding_density,
+        }
+    }
+}
+
+pub fn summary(project: PathBuf) -> anyhow::Result<()> {
+    let project = load(project)?;
+
+    let mut wtr = tsv_writer();
+    for bin in project.bins.values() {
+        wtr.serialize(BinSummaryRow::from_bin(bin))?;
+    }
+    wtr.flush()?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ScoreRow<'a> {
+    bin: &'a str,
+    contig: &'a str,
+    score: Option<f64>,
+    n_motifs: usize,
+    current: Assignment,
+    suggestion: Assignment,
+    coverage_deviation: Option<f64>,
+    coverage_outlier: bool,
+}
+
+pub fn score(
+    project: PathBuf,
+    bins: Vec<String>,
+    scoring: ScoringArgs,
+    apply: bool,
+    overwrite: bool,
+    user: Option<String>,
+) -> anyhow::Result<()> {
+    let mut project = load(project)?;
+
+    let defaults = ScoringOptions::default();
+    let options = ScoringOptions {
+        min_n_motif_obs: scoring.min_n_motif_obs.unwrap_or(defaults.min_n_motif_obs),
+        min_shared_motifs: scoring
+            .min_shared_motifs
+            .unwrap_or(defaults.min_shared_motifs),
+        ambiguous_threshold: scoring
+            .ambiguous_threshold
+            .unwrap_or(defaults.ambiguous_threshold),
+        contamination_threshold: scoring
+            .contamination_threshold
+            .unwrap_or(defaults.contamination_threshold),
+        coverage_threshold: scoring
+            .coverage_threshold
+            .unwrap_or(defaults.coverage_threshold),
+    };
+
+    let bins = if bins.is_empty() {
+        project.bins.keys().map(|b| b.0.clone()).collect()
+    } else {
+        bins
+    };
+
+    let mut wtr = tsv_writer();
+    let mut suggestions = Vec::new();
+    for bin in &bins {
+        let scores = project.score_contamination(bin, &options)?;
+        for score in &scores {
+            wtr.serialize(ScoreRow {
+                bin,
+                contig: &score.contig_id.0,
+                score: score.score,
+                n_motifs: score.n_motifs,
+                current: score.current,
+                suggestion: score.suggestion,
+                coverage_deviation: score.coverage_deviation,
+                coverage_outlier: score.coverage_outlier,
+            })?;
+        }
+        suggestions.extend(
+            scores
+                .into_iter()
+                .filter(|s| s.suggestion != Assignment::None && s.suggestion != s.current)
+                // Assignments made by a curator are kept unless asked otherwise.
+                .filter(|s| overwrite || s.current == Assignment::None)
+                .map(|s| (BinId(bin.clone()), s.contig_id, s.suggestion)),
+        );
+    }
+    wtr.flush()?;
+
+    if apply {
+        let n_applied = suggestions.len();
+        for (bin, contig_id, assignment) in suggestions {
+            project.set_assignment(&bin, &contig_id, assignment, user.as_deref())?;
+        }
+        project.save_metadata()?;
+        eprintln!("Applied {} suggested assignments", n_applied);
+    }
+
+    Ok(())
+}
+
+pub fn apply(project: PathBuf, decisions: PathBuf, user: Option<String>) -> anyhow::Result<()> {
+    let mut project = load(project)?;
+
+    let records = AssignmentReader::new(&decisions)?
+        .read_all()
+        .with_context(|| format!("Could not read decisions '{}'", decisions.display()))?;
+
+    for record in &records {
+        project.set_assignment(
+            &BinId(record.bin.clone()),
+            &ContigId(record.contig.clone()),
+            record.assignment,
+            user.as_deref(),
+        )?;
+    }
+    project.save_metadata()?;
+
+    eprintln!("Applied {} assignments", records.len());
+    Ok(())
+}
+
+pub struct ExportTargets {
+    pub fasta: bool,
+    pub fasta_dir: Option<PathBuf>,
+    pub contig_bin: bool,
+    pub contig_bin_path: Option<PathBuf>,
+}
+
+pub fn export(
+    project: PathBuf,
+    targets: ExportTargets,
+    drop_ambiguous: bool,
+) -> anyhow::Result<()> {
+    let project = load(project)?;
+
+    let policy = if drop_ambiguous {
+        AmbiguousPolicy::Drop
+    } else {
+        AmbiguousPolicy::Keep
+    };
+    let export_all = !targets.fasta && !targets.contig_bin;
+
+    if targets.fasta && project.assembly_path.is_none() {
+        bail!("Project '{}' has no assembly_path set", project.id);
+    }
+
+    let mut stderr = std::io::stderr();
+    if targets.contig_bin || export_all {
+        let exported = project.export_contig_bin(targets.contig_bin_path, policy)?;
+        writeln!(
+            stderr,
+            "Wrote {} contigs in {} bins to {}",
+            exported.n_contigs,
+            exported.n_bins,
+            exported.path.display()
+        )?;
+    }
+
+    if targets.fasta || (export_all && project.assembly_path.is_some()) {
+        let exported = project.export_fasta(targets.fasta_dir, policy)?;
+        for bin in exported {
+            if !bin.missing_contigs.is_empty() {
+                tracing::warn!(
+                    "{} contigs of bin '{}' were not found in the assembly",
+                    bin.missing_contigs.len(),
+                    bin.bin_id.0
+                );
+            }
+            if let Some(path) = bin.path {
+                writeln!(
+                    stderr,
+                    "Wrote {} contigs to {}",
+                    bin.n_contigs,
+                    path.display()
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+pub mod commands;
+
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use tracing_subscriber::EnvFilter;
+
+/// Headless curation of contam-map projects.
+#[derive(Parser)]
+#[command(name = "mag_cli", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print one line per bin with quality and assignment counts.
+    Summary {
+        /// Path to the project.toml
+        project: PathBuf,
+    },
+    /// Print contamination scores for every contig.
+    Score {
+        /// Path to the project.toml
+        project: PathBuf,
+
+        /// Only score these bins
+        #[arg(long = "bin")]
+        bins: Vec<String>,
+
+        #[command(flatten)]
+        scoring: ScoringArgs,
+
+        /// Store the suggested assignments in the project. Only contigs
+        /// without an assignment are changed unless --overwrite is given.
+        #[arg(long)]
+        apply: bool,
+
+        /// With --apply, also replace assignments that were already set
+        #[arg(long, requires = "apply")]
+        overwrite: bool,
+
+        /// Name recorded in the project history
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Apply assignments from a TSV with `bin`, `contig` and `assignment` columns.
+    Apply {
+        /// Path to the project.toml
+        project: PathBuf,
+
+        /// Decisions TSV
+        decisions: PathBuf,
+
+        /// Name recorded in the project history
+        #[arg(long)]
+        user: Option<String>,
+    },
+    /// Export refined bins. Exports everything available when no format is given.
+    Export {
+        /// Path to the project.toml
+        project: PathBuf,
+
+        /// Write one FASTA per bin
+        #[arg(long)]
+        fasta: bool,
+
+        /// Directory for the FASTA files [default: <output_path>/bins]
+        #[arg(long)]
+        fasta_dir: Option<PathBuf>,
+
+        /// Write a refined contig_bin TSV
+        #[arg(long)]
+        contig_bin: bool,
+
+        /// Path of the contig_bin TSV [default: <output_path>/contig_bin_refined.tsv]
+        #[arg(long)]
+        contig_bin_path: Option<PathBuf>,
+
+        /// Leave out contigs marked Ambiguous
+        #[arg(long)]
+        drop_ambiguous: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct ScoringArgs {
+    #[arg(long)]
+    pub min_n_motif_obs: Option<u32>,
+
+    #[arg(long)]
+    pub min_shared_motifs: Option<usize>,
+
+    #[arg(long)]
+    pub ambiguous_threshold: Option<f64>,
+
+    #[arg(long)]
+    pub contamination_threshold: Option<f64>,
+
+    #[arg(long)]
+    pub coverage_threshold: Option<f64>,
+}
+
+fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .compact()
+        .init();
+
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Summary { project } => commands::summary(project),
+        Command::Score {
+            project,
+            bins,
+            scoring,
+            apply,
+            overwrite,
+            user,
+        } => commands::score(project, bins, scoring, apply, overwrite, user),
+        Command::Apply {
+            project,
+            decisions,
+            user,
+        } => commands::apply(project, decisions, user),
+        Command::Export {
+            project,
+            fasta,
+            fasta_dir,
+            contig_bin,
+            contig_bin_path,
+            drop_ambiguous,
+        } => commands::export(
+            project,
+            commands::ExportTargets {
+                fasta,
+                fasta_dir,
+                contig_bin,
+                contig_bin_path,
+            },
+            drop_ambiguous,
+        ),
+    }
+}
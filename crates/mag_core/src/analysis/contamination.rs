@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    analysis::{coverage::coverage_deviations, profile::ConsensusProfile},
+    bin::Bin,
+    contig::{Assignment, Contig, ContigId},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+#[serde(default)]
+pub struct ScoringOptions {
+    pub min_n_motif_obs: u32,
+    pub min_shared_motifs: usize,
+    pub ambiguous_threshold: f64,
+    pub contamination_threshold: f64,
+    /// Coverage deviation, in log2 fold change, from which a contig is a
+    /// coverage outlier in its bin.
+    pub coverage_threshold: f64,
+}
+
+impl Default for ScoringOptions {
+    fn default() -> Self {
+        Self {
+            min_n_motif_obs: 3,
+            min_shared_motifs: 2,
+            ambiguous_threshold: 0.15,
+            contamination_threshold: 0.3,
+            coverage_threshold: 1.0,
+        }
+    }
+}
+
+impl ScoringOptions {
+    pub fn suggest(&self, score: f64) -> Assignment {
+        match score {
+            s if s >= self.contamination_threshold => Assignment::Contamination,
+            s if s >= self.ambiguous_threshold => Assignment::Ambiguous,
+            _ => Assignment::Clean,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigScore {
+    pub contig_id: ContigId,
+    pub score: Option<f64>,
+    pub n_motifs: usize,
+    pub current: Assignment,
+    pub suggestion: Assignment,
+    /// Deviation of the contig's depth profile from the bin, see
+    /// `CoverageProfile::deviation`.
+    pub coverage_deviation: Option<f64>,
+    pub coverage_outlier: bool,
+}
+
+/// Score every contig in `bin` against the consensus methylation profile of
+/// the remaining contigs. Contigs without enough shared motifs get no score
+/// and no suggestion. The coverage deviation is reported alongside but does
+/// not change the suggestion.
+pub fn score_bin(
+    bin: &Bin,
+    contigs: &HashMap<ContigId, Contig>,
+    options: &ScoringOptions,
+) -> Vec<ContigScore> {
+    let profile = ConsensusProfile::from_contigs(
+        bin.contig_metadata
+            .iter()
+            .filter_map(|c| contigs.get(&c.contig_id)),
+        options.min_n_motif_obs,
+    );
+    let coverage = coverage_deviations(
+        bin.contig_metadata
+            .iter()
+            .filter_map(|c| contigs.get(&c.contig_id)),
+    );
+
+    bin.contig_metadata
+        .iter()
+        .map(|c| {
+            let distance = contigs
+                .get(&c.contig_id)
+                .and_then(|contig| profile.distance(contig, true))
+                .filter(|(_, n_motifs)| *n_motifs >= options.min_shared_motifs);
+
+            ContigScore {
+                contig_id: c.contig_id.clone(),
+                score: distance.map(|(d, _)| d),
+                n_motifs: distance.map_or(0, |(_, n)| n),
+                current: c.assignment,
+                suggestion: distance.map_or(Assignment::None, |(d, _)| options.suggest(d)),
+                coverage_deviation: coverage.get(c.contig_id.0.as_str()).copied(),
+                coverage_outlier: coverage
+                    .get(c.contig_id.0.as_str())
+                    .is_some_and(|d| *d >= options.coverage_threshold),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use epimetheus_methylome::Motif;
+
+    use super::*;
+    use crate::{bin::BinId, contig::ContigAssignment, methylation::MotifSignature};
+
+    const MOTIFS: [(&str, &str, u8); 4] = [
+        ("GATC", "a", 1),
+        ("CCWGG", "m", 1),
+        ("GANTC", "a", 1),
+        ("TCGA", "a", 3),
+    ];
+
+    fn contig(id: &str, values: &[f64]) -> (ContigId, Contig) {
+        let motifs = values
+            .iter()
+            .enumerate()
+            .map(|(i, &methylation_value)| {
+                let (sequence, mod_type, position) = MOTIFS[i];
+                let motif = Arc::new(Motif::new(sequence, mod_type, position).unwrap());
+                let signature = MotifSignature {
+                    motif: Arc::clone(&motif),
+                    methylation_value,
+                    n_motif_obs: 20,
+                    mean_coverage: 30.0,
+                };
+                (motif, signature)
+            })
+            .collect();
+        let contig_id = ContigId(id.to_string());
+        let contig = Contig {
+            contig_id: contig_id.clone(),
+            motifs,
+            mean_coverage: 30.0,
+            sequence: None,
+            sample_depths: None,
+            taxonomy: None,
+        };
+        (contig_id, contig)
+    }
+
+    fn bin(ids: &[&str]) -> Bin {
+        let mut bin = Bin::new(BinId("bin".to_string()));
+        bin.contig_metadata = ids
+            .iter()
+            .map(|id| ContigAssignment::new(ContigId(id.to_string()), Assignment::None))
+            .collect();
+        bin
+    }
+
+    fn score<'a>(scores: &'a [ContigScore], id: &str) -> &'a ContigScore {
+        scores.iter().find(|s| s.contig_id.0 == id).unwrap()
+    }
+
+    #[test]
+    fn planted_outlier_is_the_only_contamination() {
+        let contigs: HashMap<ContigId, Contig> = [
+            contig("c1", &[0.90, 0.80, 0.05, 0.10]),
+            contig("c2", &[0.88, 0.82, 0.04, 0.12]),
+            contig("c3", &[0.92, 0.78, 0.06, 0.09]),
+            contig("c4", &[0.91, 0.81, 0.03, 0.11]),
+            contig("c5", &[0.89, 0.79, 0.05, 0.13]),
+            contig("plasmid", &[0.10, 0.05, 0.85, 0.90]),
+        ]
+        .into_iter()
+        .collect();
+        let ids = ["c1", "c2", "c3", "c4", "c5", "plasmid"];
+        let scores = score_bin(&bin(&ids), &contigs, &ScoringOptions::default());
+
+        assert_eq!(scores.len(), 6);
+        let outlier = score(&scores, "plasmid");
+        assert_eq!(outlier.suggestion, Assignment::Contamination);
+        assert_eq!(outlier.n_motifs, 4);
+        assert!(outlier.score.unwrap() > 0.7, "{:?}", outlier);
+        for id in &ids[..5] {
+            let clean = score(&scores, id);
+            assert_eq!(clean.suggestion, Assignment::Clean, "{:?}", clean);
+            assert!(clean.score.unwrap() < 0.05, "{:?}", clean);
+        }
+        // The weighted median keeps the outlier from shifting the consensus.
+        let worst_clean = ids[..5]
+            .iter()
+            .map(|id| score(&scores, id).score.unwrap())
+            .fold(0.0, f64::max);
+        assert!(worst_clean < outlier.score.unwrap() / 10.0);
+    }
+
+    #[test]
+    fn contigs_without_enough_shared_motifs_are_not_scored() {
+        let contigs: HashMap<ContigId, Contig> = [
+            contig("c1", &[0.90, 0.80]),
+            contig("c2", &[0.88, 0.82]),
+            contig("sparse", &[0.90]),
+        ]
+        .into_iter()
+        .collect();
+        let scores = score_bin(
+            &bin(&["c1", "c2", "sparse", "missing"]),
+            &contigs,
+            &ScoringOptions::default(),
+        );
+
+        for id in ["sparse", "missing"] {
+            let unscored = score(&scores, id);
+            assert_eq!(unscored.score, None);
+            assert_eq!(unscored.n_motifs, 0);
+            assert_eq!(unscored.suggestion, Assignment::None);
+        }
+        assert_eq!(score(&scores, "c1").n_motifs, 2);
+    }
+
+    #[test]
+    fn thresholds() {
+        let options = ScoringOptions::default();
+
+        assert_eq!(options.suggest(0.0), Assignment::Clean);
+        assert_eq!(options.suggest(0.149), Assignment::Clean);
+        assert_eq!(options.suggest(0.15), Assignment::Ambiguous);
+        assert_eq!(options.suggest(0.299), Assignment::Ambiguous);
+        assert_eq!(options.suggest(0.3), Assignment::Contamination);
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::contig::Contig;
+
+/// Added to depths before taking the log so zero coverage stays finite.
+const PSEUDO_DEPTH: f64 = 0.1;
+
+/// Median log2 depth of a bin in each sample. Contigs without depths are
+/// skipped.
+#[derive(Debug, Clone)]
+pub struct CoverageProfile {
+    log_depths: Vec<f64>,
+}
+
+impl CoverageProfile {
+    pub fn from_contigs<'a>(contigs: impl Iterator<Item = &'a Contig>) -> Option<Self> {
+        let mut per_sample: Vec<Vec<f64>> = Vec::new();
+        for depths in contigs.filter_map(|c| c.sample_depths.as_ref()) {
+            if per_sample.is_empty() {
+                per_sample = vec![Vec::new(); depths.len()];
+            }
+            for (sample, depth) in per_sample.iter_mut().zip(depths) {
+                sample.push(log_depth(*depth));
+            }
+        }
+
+        let log_depths = per_sample
+            .into_iter()
+            .map(|mut values| {
+                values.sort_by(f64::total_cmp);
+                median(&values)
+            })
+            .collect::<Option<Vec<f64>>>()?;
+
+        Some(Self { log_depths })
+    }
+
+    /// Root mean square of the log2 fold change between the contig and the
+    /// profile across samples. A deviation of 1 means the contig is on average
+    /// twice or half as deep as the rest of the bin.
+    pub fn deviation(&self, contig: &Contig) -> Option<f64> {
+        let depths = contig.sample_depths.as_ref()?;
+        if depths.len() != self.log_depths.len() || depths.is_empty() {
+            return None;
+        }
+
+        let sum_square: f64 = depths
+            .iter()
+            .zip(&self.log_depths)
+            .map(|(depth, expected)| (log_depth(*depth) - expected).powi(2))
+            .sum();
+        Some((sum_square / depths.len() as f64).sqrt())
+    }
+}
+
+/// Coverage deviation of every contig in `contigs` from their shared profile.
+pub fn coverage_deviations<'a>(
+    contigs: impl Iterator<Item = &'a Contig> + Clone,
+) -> HashMap<&'a str, f64> {
+    let Some(profile) = CoverageProfile::from_contigs(contigs.clone()) else {
+        return HashMap::new();
+    };
+
+    contigs
+        .filter_map(|c| Some((c.contig_id.0.as_str(), profile.deviation(c)?)))
+        .collect()
+}
+
+fn log_depth(depth: f64) -> f64 {
+    (depth.max(0.0) + PSEUDO_DEPTH).log2()
+}
+
+fn median(sorted: &[f64]) -> Option<f64> {
+    let n = sorted.len();
+    match n {
+        0 => None,
+        n if n % 2 == 1 => Some(sorted[n / 2]),
+        n => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+    }
+}
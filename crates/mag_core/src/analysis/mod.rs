@@ -0,0 +1,6 @@
+pub mod contamination;
+pub mod coverage;
+pub mod profile;
+pub mod reassignment;
+pub mod recruitment;
+pub mod taxonomy;
@@ -0,0 +1,286 @@
+use std::{collections::HashMap, sync::Arc};
+
+use epimetheus_methylome::Motif;
+
+use crate::{
+    contig::{Contig, ContigId},
+    methylation::MotifSignature,
+};
+
+/// Weight of a single motif observation. Both the number of motif
+/// observations and the read coverage are log-dampened so a single deeply
+/// sequenced contig cannot dominate a bin.
+pub fn signature_weight(signature: &MotifSignature) -> f64 {
+    (signature.n_motif_obs as f64).ln_1p() * signature.mean_coverage.max(0.0).ln_1p()
+}
+
+#[derive(Debug, Clone)]
+pub struct WeightedObservation {
+    pub contig_id: ContigId,
+    pub methylation_value: f64,
+    pub weight: f64,
+}
+
+/// All observations of one motif within a profile, sorted by methylation
+/// value. The consensus is the weighted median, which keeps a few
+/// contaminating contigs from dragging the profile towards themselves.
+#[derive(Debug, Clone, Default)]
+pub struct ConsensusValue {
+    pub observations: Vec<WeightedObservation>,
+}
+
+impl ConsensusValue {
+    pub fn n_contigs(&self) -> usize {
+        self.observations.len()
+    }
+
+    pub fn methylation_value(&self) -> Option<f64> {
+        weighted_median(self.observations.iter())
+    }
+
+    /// Consensus value with the observation of `contig_id` removed, so a
+    /// contig is never compared against a profile it is part of.
+    pub fn methylation_value_excluding(&self, contig_id: &ContigId) -> Option<f64> {
+        weighted_median(
+            self.observations
+                .iter()
+                .filter(|o| &o.contig_id != contig_id),
+        )
+    }
+}
+
+fn weighted_median<'a>(observations: impl Iterator<Item = &'a WeightedObservation>) -> Option<f64> {
+    let observations = observations.collect::<Vec<_>>();
+    let total_weight = observations.iter().map(|o| o.weight).sum::<f64>();
+    if total_weight <= 0.0 {
+        return None;
+    }
+
+    let mut cumulative = 0.0;
+    for observation in &observations {
+        cumulative += observation.weight;
+        if cumulative >= total_weight / 2.0 {
+            return Some(observation.methylation_value);
+        }
+    }
+    observations.last().map(|o| o.methylation_value)
+}
+
+/// Consensus methylation profile of a set of contigs.
+#[derive(Debug, Clone, Default)]
+pub struct ConsensusProfile {
+    pub motifs: HashMap<Arc<Motif>, ConsensusValue>,
+    pub min_n_motif_obs: u32,
+}
+
+impl ConsensusProfile {
+    pub fn from_contigs<'a>(
+        contigs: impl IntoIterator<Item = &'a Contig>,
+        min_n_motif_obs: u32,
+    ) -> Self {
+        let mut motifs: HashMap<Arc<Motif>, ConsensusValue> = HashMap::new();
+        for contig in contigs {
+            for signature in contig.motifs.values() {
+                let weight = signature_weight(signature);
+                if signature.n_motif_obs < min_n_motif_obs || weight <= 0.0 {
+                    continue;
+                }
+                motifs
+                    .entry(Arc::clone(&signature.motif))
+                    .or_default()
+                    .observations
+                    .push(WeightedObservation {
+                        contig_id: contig.contig_id.clone(),
+                        methylation_value: signature.methylation_value,
+                        weight,
+                    });
+            }
+        }
+
+        for value in motifs.values_mut() {
+            value
+                .observations
+                .sort_by(|a, b| a.methylation_value.total_cmp(&b.methylation_value));
+        }
+
+        Self {
+            motifs,
+            min_n_motif_obs,
+        }
+    }
+
+    /// Weighted root-mean-square difference between `contig` and the profile
+    /// over the motifs both have data for. Returns the distance and the number
+    /// of motifs it was based on.
+    pub fn distance(&self, contig: &Contig, leave_out: bool) -> Option<(f64, usize)> {
+        let mut sum_square = 0.0;
+        let mut total_weight = 0.0;
+        let mut n_motifs = 0;
+
+        for signature in contig.motifs.values() {
+            if signature.n_motif_obs < self.min_n_motif_obs {
+                continue;
+            }
+            let Some(consensus) = self.motifs.get(&signature.motif) else {
+                continue;
+            };
+            let consensus_value = if leave_out {
+                consensus.methylation_value_excluding(&contig.contig_id)
+            } else {
+                consensus.methylation_value()
+            };
+            let Some(consensus_value) = consensus_value else {
+                continue;
+            };
+
+            let weight = signature_weight(signature);
+            sum_square += weight * (signature.methylation_value - consensus_value).powi(2);
+            total_weight += weight;
+            n_motifs += 1;
+        }
+
+        (total_weight > 0.0).then(|| ((sum_square / total_weight).sqrt(), n_motifs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOTIFS: [(&str, &str, u8); 3] = [("GATC", "a", 1), ("CCWGG", "m", 1), ("GANTC", "a", 1)];
+
+    fn motif(i: usize) -> Arc<Motif> {
+        let (sequence, mod_type, position) = MOTIFS[i];
+        Arc::new(Motif::new(sequence, mod_type, position).unwrap())
+    }
+
+    /// Contig with `(methylation value, n_motif_obs, mean coverage)` for the
+    /// first motifs of `MOTIFS`.
+    fn contig(id: &str, values: &[(f64, u32, f64)]) -> Contig {
+        let motifs = values
+            .iter()
+            .enumerate()
+            .map(|(i, &(methylation_value, n_motif_obs, mean_coverage))| {
+                let signature = MotifSignature {
+                    motif: motif(i),
+                    methylation_value,
+                    n_motif_obs,
+                    mean_coverage,
+                };
+                (motif(i), signature)
+            })
+            .collect();
+        Contig {
+            contig_id: ContigId(id.to_string()),
+            motifs,
+            mean_coverage: 0.0,
+            sequence: None,
+            sample_depths: None,
+            taxonomy: None,
+        }
+    }
+
+    fn consensus(values: &[(&str, f64, f64)]) -> ConsensusValue {
+        ConsensusValue {
+            observations: values
+                .iter()
+                .map(|&(id, methylation_value, weight)| WeightedObservation {
+                    contig_id: ContigId(id.to_string()),
+                    methylation_value,
+                    weight,
+                })
+                .collect(),
+        }
+    }
+
+    fn weight(n_motif_obs: u32, mean_coverage: f64) -> f64 {
+        signature_weight(&MotifSignature {
+            motif: motif(0),
+            methylation_value: 0.5,
+            n_motif_obs,
+            mean_coverage,
+        })
+    }
+
+    #[test]
+    fn weights_are_log_dampened() {
+        assert_eq!(weight(0, 30.0), 0.0);
+        assert_eq!(weight(10, 0.0), 0.0);
+        assert_eq!(weight(10, -5.0), 0.0);
+        assert!((weight(9, 99.0) - 10f64.ln() * 100f64.ln()).abs() < 1e-12);
+        // Ten times the coverage is far less than ten times the weight.
+        assert!(weight(20, 300.0) < 2.0 * weight(20, 30.0));
+    }
+
+    #[test]
+    fn weighted_median() {
+        let even = consensus(&[("a", 0.1, 1.0), ("b", 0.5, 1.0), ("c", 0.9, 1.0)]);
+        assert_eq!(even.methylation_value(), Some(0.5));
+
+        let heavy = consensus(&[("a", 0.1, 1.0), ("b", 0.5, 1.0), ("c", 0.9, 5.0)]);
+        assert_eq!(heavy.methylation_value(), Some(0.9));
+
+        // Half the weight reached exactly at the first value.
+        let split = consensus(&[("a", 0.2, 2.0), ("b", 0.8, 2.0)]);
+        assert_eq!(split.methylation_value(), Some(0.2));
+
+        assert_eq!(consensus(&[]).methylation_value(), None);
+        assert_eq!(consensus(&[("a", 0.3, 0.0)]).methylation_value(), None);
+    }
+
+    #[test]
+    fn leave_one_out() {
+        let value = consensus(&[("a", 0.1, 1.0), ("b", 0.5, 1.0), ("c", 0.9, 5.0)]);
+        let id = |s: &str| ContigId(s.to_string());
+
+        assert_eq!(value.methylation_value_excluding(&id("c")), Some(0.1));
+        assert_eq!(value.methylation_value_excluding(&id("a")), Some(0.9));
+        assert_eq!(value.methylation_value_excluding(&id("x")), Some(0.9));
+        assert_eq!(
+            consensus(&[("a", 0.1, 1.0)]).methylation_value_excluding(&id("a")),
+            None
+        );
+    }
+
+    #[test]
+    fn profile_skips_sparse_and_weightless_observations() {
+        let contigs = [
+            contig("a", &[(0.8, 10, 20.0), (0.1, 2, 20.0)]),
+            contig("b", &[(0.9, 10, 0.0), (0.2, 10, 20.0)]),
+        ];
+        let profile = ConsensusProfile::from_contigs(&contigs, 3);
+
+        let gatc = &profile.motifs[&motif(0)];
+        assert_eq!(gatc.n_contigs(), 1);
+        assert_eq!(gatc.methylation_value(), Some(0.8));
+        let ccwgg = &profile.motifs[&motif(1)];
+        assert_eq!(ccwgg.n_contigs(), 1);
+        assert_eq!(ccwgg.methylation_value(), Some(0.2));
+    }
+
+    #[test]
+    fn distance_is_a_weighted_rms() {
+        let members = [
+            contig("a", &[(0.8, 10, 20.0), (0.1, 10, 20.0)]),
+            contig("b", &[(0.8, 10, 20.0), (0.1, 10, 20.0)]),
+        ];
+        let profile = ConsensusProfile::from_contigs(&members, 3);
+
+        // Equal weights: sqrt((0.3^2 + 0.1^2) / 2)
+        let probe = contig("p", &[(0.5, 10, 20.0), (0.2, 10, 20.0), (0.7, 10, 20.0)]);
+        let (distance, n_motifs) = profile.distance(&probe, false).unwrap();
+        assert_eq!(n_motifs, 2);
+        assert!((distance - (0.05f64).sqrt()).abs() < 1e-12);
+
+        // Below `min_n_motif_obs` the motif is ignored.
+        let sparse = contig("p", &[(0.5, 10, 20.0), (0.2, 1, 20.0)]);
+        let (distance, n_motifs) = profile.distance(&sparse, false).unwrap();
+        assert_eq!(n_motifs, 1);
+        assert!((distance - 0.3).abs() < 1e-12);
+
+        // A contig alone in the profile has nothing to compare against.
+        let alone = ConsensusProfile::from_contigs(&members[..1], 3);
+        assert_eq!(alone.distance(&members[0], true), None);
+        assert_eq!(alone.distance(&members[0], false).unwrap().0, 0.0);
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    analysis::{contamination::ScoringOptions, profile::ConsensusProfile},
+    bin::{Bin, BinId},
+    contig::{Assignment, Contig, ContigId},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct BinCandidate {
+    pub bin_id: BinId,
+    pub distance: f64,
+    pub n_motifs: usize,
+}
+
+/// Consensus profile of a bin, leaving out contigs already flagged as
+/// contamination.
+pub fn bin_profile(
+    bin: &Bin,
+    contigs: &HashMap<ContigId, Contig>,
+    options: &ScoringOptions,
+) -> ConsensusProfile {
+    ConsensusProfile::from_contigs(
+        bin.contig_metadata
+            .iter()
+            .filter(|c| c.assignment != Assignment::Contamination)
+            .filter_map(|c| contigs.get(&c.contig_id)),
+        options.min_n_motif_obs,
+    )
+}
+
+/// Rank all bins that do not already contain `contig` by the distance between
+/// the contig and the bin's consensus profile, closest first.
+pub fn rank_candidate_bins(
+    contig: &Contig,
+    bins: &BTreeMap<BinId, Bin>,
+    contigs: &HashMap<ContigId, Contig>,
+    options: &ScoringOptions,
+) -> Vec<BinCandidate> {
+    let mut candidates = bins
+        .values()
+        .filter(|bin| !bin.contains(&contig.contig_id))
+        .filter_map(|bin| {
+            let (distance, n_motifs) =
+                bin_profile(bin, contigs, options).distance(contig, false)?;
+            (n_motifs >= options.min_shared_motifs).then(|| BinCandidate {
+                bin_id: bin.id.clone(),
+                distance,
+                n_motifs,
+            })
+        })
+        .collect::<Vec<BinCandidate>>();
+
+    candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    candidates
+}
@@ -0,0 +1,89 @@
+use std::collections::{BTreeMap, HashMap};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    analysis::{
+        contamination::ScoringOptions, profile::ConsensusProfile, reassignment::BinCandidate,
+        reassignment::bin_profile,
+    },
+    bin::{Bin, BinId},
+    contig::{Contig, ContigId},
+};
+
+/// Contig with methylation data that is not in any bin.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct UnbinnedContig {
+    pub contig_id: ContigId,
+    pub mean_coverage: f64,
+    pub n_motifs: usize,
+}
+
+impl UnbinnedContig {
+    pub fn from_contig(contig: &Contig) -> Self {
+        Self {
+            contig_id: contig.contig_id.clone(),
+            mean_coverage: contig.mean_coverage,
+            n_motifs: contig.motifs.len(),
+        }
+    }
+}
+
+/// Closest bin for an unbinned contig. `recruit` is set when the contig is
+/// as close to the bin as a clean member would be.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct Recruitment {
+    pub contig_id: ContigId,
+    pub best: BinCandidate,
+    pub runner_up: Option<BinCandidate>,
+    pub recruit: bool,
+}
+
+/// Find the closest bin for each of `unbinned`, closest first. Contigs that
+/// share too few motifs with every bin are left out.
+pub fn rank_recruits(
+    unbinned: &[&Contig],
+    bins: &BTreeMap<BinId, Bin>,
+    contigs: &HashMap<ContigId, Contig>,
+    options: &ScoringOptions,
+) -> Vec<Recruitment> {
+    // Every contig is compared with every bin, so build the profiles once.
+    let profiles: Vec<(&BinId, ConsensusProfile)> = bins
+        .values()
+        .map(|bin| (&bin.id, bin_profile(bin, contigs, options)))
+        .collect();
+
+    let mut recruits: Vec<Recruitment> = unbinned
+        .par_iter()
+        .filter_map(|contig| {
+            let mut candidates: Vec<BinCandidate> = profiles
+                .iter()
+                .filter_map(|(bin_id, profile)| {
+                    let (distance, n_motifs) = profile.distance(contig, false)?;
+                    (n_motifs >= options.min_shared_motifs).then(|| BinCandidate {
+                        bin_id: (*bin_id).clone(),
+                        distance,
+                        n_motifs,
+                    })
+                })
+                .collect();
+            candidates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+            let mut candidates = candidates.into_iter();
+            let best = candidates.next()?;
+            Some(Recruitment {
+                contig_id: contig.contig_id.clone(),
+                recruit: best.distance < options.ambiguous_threshold,
+                best,
+                runner_up: candidates.next(),
+            })
+        })
+        .collect();
+
+    recruits.sort_by(|a, b| a.best.distance.total_cmp(&b.best.distance));
+    recruits
+}
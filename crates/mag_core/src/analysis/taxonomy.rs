@@ -0,0 +1,50 @@
+/// Taxon names of a label, from the broadest rank to the most specific.
+/// Rank prefixes (`g__`, `g_`), GTDB placeholder suffixes (`_A`) and taxids
+/// are removed, so labels from different classifiers compare.
+pub fn taxon_names(label: &str) -> Vec<String> {
+    label
+        .split(';')
+        .filter_map(|name| {
+            let mut name = name.trim();
+            if let Some((_, rest)) = name.split_once("__") {
+                name = rest;
+            } else if name.len() > 2 && name.as_bytes()[1] == b'_' {
+                name = &name[2..];
+            }
+            if let Some(i) = name.find(" (taxid") {
+                name = &name[..i];
+            }
+            let name = name
+                .split(' ')
+                .map(strip_placeholder)
+                .collect::<Vec<&str>>()
+                .join(" ");
+            (!name.is_empty() && !name.starts_with("taxid")).then(|| name.to_lowercase())
+        })
+        .collect()
+}
+
+fn strip_placeholder(word: &str) -> &str {
+    match word.rsplit_once('_') {
+        Some((base, suffix))
+            if !base.is_empty()
+                && !suffix.is_empty()
+                && suffix.chars().all(|c| c.is_ascii_uppercase()) =>
+        {
+            base
+        }
+        _ => word,
+    }
+}
+
+/// Compare a contig label with the label of its bin. They agree if the most
+/// specific name of either appears in the other. `None` if either label has
+/// no names to compare, e.g. bare taxids.
+pub fn labels_agree(contig: &str, bin: &str) -> Option<bool> {
+    let contig = taxon_names(contig);
+    let bin = taxon_names(bin);
+
+    let agrees = contig.last().is_some_and(|name| bin.contains(name))
+        || bin.last().is_some_and(|name| contig.contains(name));
+    (!contig.is_empty() && !bin.is_empty()).then_some(agrees)
+}
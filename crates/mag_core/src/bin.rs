@@ -8,13 +8,59 @@ use ts_rs::TS;
 
 use crate::{
     contig::{Assignment, ContigAssignment, ContigId},
-    io::reader::{checkm2::Checkm2Record, contig_bin::ContigBinRecord},
+    errors::DataError,
+    io::reader::{contig_bin::ContigBinRecord, quality::QualityRecord},
 };
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, TS)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
 pub struct BinId(pub String);
 
+/// What to do when the contig_bin file assigns a contig to more than one bin.
+/// Rows repeating the same contig and bin are always merged.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS, Default, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum DuplicatePolicy {
+    /// Refuse to create the project.
+    Error,
+    /// Keep the contig in the first bin it is listed in.
+    KeepFirst,
+    /// Keep the contig in every bin. The conflicts are reported by
+    /// `find_conflicts` until resolved.
+    #[default]
+    KeepAll,
+}
+
+/// Contig that is in more than one bin.
+#[derive(Serialize, Deserialize, Debug, Clone, TS, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigConflict {
+    pub contig_id: ContigId,
+    pub bins: Vec<BinId>,
+}
+
+/// Contigs found in more than one bin, sorted by contig id.
+pub fn find_conflicts(bins: &BTreeMap<BinId, Bin>) -> Vec<ContigConflict> {
+    let mut contig_bins: BTreeMap<&str, Vec<BinId>> = BTreeMap::new();
+    for bin in bins.values() {
+        for contig in &bin.contig_metadata {
+            contig_bins
+                .entry(&contig.contig_id.0)
+                .or_default()
+                .push(bin.id.clone());
+        }
+    }
+
+    contig_bins
+        .into_iter()
+        .filter(|(_, bins)| bins.len() > 1)
+        .map(|(contig_id, bins)| ContigConflict {
+            contig_id: ContigId(contig_id.to_string()),
+            bins,
+        })
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, TS)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
 pub struct Bin {
@@ -23,20 +69,180 @@ pub struct Bin {
     pub completeness: Option<f64>,
     pub contamination: Option<f64>,
     pub quality: Option<BinQuality>,
+    /// Lineage from a bin classifier such as GTDB-Tk.
+    #[serde(default)]
+    pub taxonomy: Option<String>,
+    /// rRNA and tRNA counts, if the project has them.
+    #[serde(default)]
+    pub rna: Option<RnaCounts>,
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub stats: BinStats,
+}
+
+/// Genome statistics reported by the quality tool, as far as it reports them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct BinStats {
+    /// Fraction of GC bases.
+    #[serde(default)]
+    pub gc_content: Option<f64>,
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub genome_size: Option<u64>,
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub n50: Option<u64>,
+    #[serde(default)]
+    #[ts(type = "number | null")]
+    pub contig_count: Option<u64>,
+    /// Fraction of the genome in predicted coding sequences.
+    #[serde(default)]
+    pub coding_density: Option<f64>,
+    /// Free text from the quality tool, e.g. CheckM2 `Additional_Notes`.
+    #[serde(default)]
+    pub quality_notes: Option<String>,
+}
+
+/// Bin attribute to sort bins by.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum BinSortKey {
+    #[default]
+    Id,
+    Completeness,
+    Contamination,
+    GcContent,
+    GenomeSize,
+    N50,
+    ContigCount,
+    CodingDensity,
+}
+
+impl BinSortKey {
+    fn value(&self, bin: &Bin) -> Option<f64> {
+        match self {
+            BinSortKey::Id => None,
+            BinSortKey::Completeness => bin.completeness,
+            BinSortKey::Contamination => bin.contamination,
+            BinSortKey::GcContent => bin.stats.gc_content,
+            BinSortKey::GenomeSize => bin.stats.genome_size.map(|v| v as f64),
+            BinSortKey::N50 => bin.stats.n50.map(|v| v as f64),
+            BinSortKey::ContigCount => bin.stats.contig_count.map(|v| v as f64),
+            BinSortKey::CodingDensity => bin.stats.coding_density,
+        }
+    }
+
+    /// Sort `bins` by the key. Bins without a value come last in either
+    /// direction, ties are broken by id.
+    pub fn sort(&self, bins: &mut [&Bin], descending: bool) {
+        bins.sort_by(|a, b| {
+            let order = match (self.value(a), self.value(b)) {
+                (Some(x), Some(y)) if descending => y.total_cmp(&x),
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            };
+            order.then_with(|| a.id.cmp(&b.id))
+        });
+    }
+}
+
+/// Bounds on bin attributes. Bins missing an attribute that is bounded are
+/// left out.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct BinFilter {
+    /// Quality tiers to keep. Any tier if empty.
+    #[serde(default)]
+    pub qualities: Vec<BinQuality>,
+    pub min_completeness: Option<f64>,
+    pub max_contamination: Option<f64>,
+    pub min_gc_content: Option<f64>,
+    pub max_gc_content: Option<f64>,
+    #[ts(type = "number | null")]
+    pub min_genome_size: Option<u64>,
+    #[ts(type = "number | null")]
+    pub min_n50: Option<u64>,
+}
+
+impl BinFilter {
+    pub fn keeps(&self, bin: &Bin) -> bool {
+        fn at_least<T: PartialOrd>(bound: Option<T>, value: Option<T>) -> bool {
+            bound.is_none_or(|b| value.is_some_and(|v| v >= b))
+        }
+        fn at_most<T: PartialOrd>(bound: Option<T>, value: Option<T>) -> bool {
+            bound.is_none_or(|b| value.is_some_and(|v| v <= b))
+        }
+
+        let quality = self.qualities.is_empty()
+            || bin
+                .quality
+                .as_ref()
+                .is_some_and(|q| self.qualities.contains(q));
+
+        quality
+            && at_least(self.min_completeness, bin.completeness)
+            && at_most(self.max_contamination, bin.contamination)
+            && at_least(self.min_gc_content, bin.stats.gc_content)
+            && at_most(self.max_gc_content, bin.stats.gc_content)
+            && at_least(self.min_genome_size, bin.stats.genome_size)
+            && at_least(self.min_n50, bin.stats.n50)
+    }
 }
 
 impl Bin {
+    /// Bin without contigs or quality.
+    pub fn new(id: BinId) -> Self {
+        Self {
+            id,
+            contig_metadata: Vec::new(),
+            completeness: None,
+            contamination: None,
+            quality: None,
+            taxonomy: None,
+            rna: None,
+            stats: BinStats::default(),
+        }
+    }
+
     pub fn from_records(
         contig_bin_records: Vec<ContigBinRecord>,
-        checkm2_records: Vec<Checkm2Record>,
-    ) -> BTreeMap<BinId, Bin> {
-        let quality_map = checkm2_records
+        quality_records: Vec<QualityRecord>,
+        policy: DuplicatePolicy,
+    ) -> Result<BTreeMap<BinId, Bin>, DataError> {
+        let quality_map = quality_records
             .iter()
             .map(|r| (r.bin.clone(), r))
-            .collect::<HashMap<String, &Checkm2Record>>();
+            .collect::<HashMap<String, &QualityRecord>>();
 
+        let mut seen: HashMap<String, Vec<String>> = HashMap::new();
+        let mut conflicts = Vec::new();
         let mut bins: BTreeMap<BinId, Bin> = BTreeMap::new();
         for cb_rec in contig_bin_records {
+            let contig_bins = seen.entry(cb_rec.contig.clone()).or_default();
+            if contig_bins.contains(&cb_rec.bin) {
+                tracing::warn!(
+                    "Contig '{}' is listed twice for bin '{}'",
+                    cb_rec.contig,
+                    cb_rec.bin
+                );
+                continue;
+            }
+            if !contig_bins.is_empty() {
+                conflicts.push(format!(
+                    "'{}' in '{}' and '{}'",
+                    cb_rec.contig,
+                    contig_bins.join("', '"),
+                    cb_rec.bin
+                ));
+                if policy == DuplicatePolicy::KeepFirst {
+                    continue;
+                }
+            }
+            contig_bins.push(cb_rec.bin.clone());
+
             let binid = BinId(cb_rec.bin.clone());
             let contig_id = ContigId(cb_rec.contig.clone());
             bins.entry(binid.clone())
@@ -47,32 +253,90 @@ impl Bin {
                     ))
                 })
                 .or_insert(Bin {
-                    id: binid,
                     contig_metadata: vec![ContigAssignment::new(
                         contig_id,
                         crate::contig::Assignment::None,
                     )],
-                    completeness: None,
-                    contamination: None,
-                    quality: None,
+                    ..Bin::new(binid)
                 });
         }
 
-        bins.into_iter()
+        if !conflicts.is_empty() {
+            let shown = conflicts
+                .iter()
+                .take(5)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("; ");
+            match policy {
+                DuplicatePolicy::Error => {
+                    return Err(DataError::DataAssertion(format!(
+                        "Found {} assignments of contigs to a second bin: {}",
+                        conflicts.len(),
+                        shown
+                    )));
+                }
+                DuplicatePolicy::KeepFirst => tracing::warn!(
+                    "Ignored {} assignments of contigs to a second bin: {}",
+                    conflicts.len(),
+                    shown
+                ),
+                DuplicatePolicy::KeepAll => tracing::warn!(
+                    "Found {} assignments of contigs to a second bin: {}",
+                    conflicts.len(),
+                    shown
+                ),
+            }
+        }
+
+        let bins = bins
+            .into_iter()
             .map(|(bin_id, bin)| {
                 let quality = quality_map.get(&bin_id.0);
 
                 let updated_bin = Bin {
                     completeness: quality.map(|q| q.completeness),
                     contamination: quality.map(|q| q.contamination),
-                    quality: quality
-                        .map(|q| BinQuality::from_values(q.completeness, q.contamination)),
+                    stats: quality.map(|q| q.stats.clone()).unwrap_or_default(),
                     ..bin
                 };
 
                 (bin_id, updated_bin)
             })
-            .collect()
+            .collect();
+
+        Ok(bins)
+    }
+
+    /// Set `quality` from completeness, contamination and rRNA/tRNA counts.
+    /// Bins without completeness or contamination have no quality.
+    /// `has_rna_input` tells whether the project has rRNA/tRNA counts at all.
+    pub fn assess_quality(&mut self, profile: &QualityProfile, has_rna_input: bool) {
+        self.quality =
+            self.completeness
+                .zip(self.contamination)
+                .map(|(completeness, contamination)| {
+                    profile.assess(
+                        completeness,
+                        contamination,
+                        self.rna.as_ref(),
+                        has_rna_input,
+                    )
+                });
+    }
+
+    pub fn contains(&self, contig_id: &ContigId) -> bool {
+        self.contig_metadata
+            .iter()
+            .any(|c| &c.contig_id == contig_id)
+    }
+
+    pub fn remove_contig(&mut self, contig_id: &ContigId) -> Option<ContigAssignment> {
+        let idx = self
+            .contig_metadata
+            .iter()
+            .position(|c| &c.contig_id == contig_id)?;
+        Some(self.contig_metadata.remove(idx))
     }
 
     pub fn to_metadata_records(&self) -> Vec<BinMetadataRecord> {
@@ -81,15 +345,59 @@ impl Bin {
             .map(|c| BinMetadataRecord {
                 id: self.id.clone(),
                 contig_id: c.contig_id.clone(),
-                assignment: c.assignment.clone(),
+                assignment: c.assignment,
                 completeness: self.completeness,
                 contamination: self.contamination,
                 quality: self.quality.clone(),
+                note: c.note.clone(),
+                tags: BinMetadataRecord::join_tags(&c.tags),
+                gc_content: self.stats.gc_content,
+                genome_size: self.stats.genome_size,
+                n50: self.stats.n50,
+                contig_count: self.stats.contig_count,
+                coding_density: self.stats.coding_density,
+                quality_notes: self.stats.quality_notes.clone(),
             })
             .collect()
     }
 }
 
+/// Move `contig_id` from bin `from` to bin `to`. The target bin is created if
+/// it does not exist. The assignment, note and tags move with the contig.
+pub fn move_contig(
+    bins: &mut BTreeMap<BinId, Bin>,
+    contig_id: &ContigId,
+    from: &BinId,
+    to: &BinId,
+) -> Result<(), DataError> {
+    if from == to {
+        return Err(DataError::DataAssertion(format!(
+            "Contig '{}' is already in bin '{}'.",
+            contig_id.0, to.0
+        )));
+    }
+    if bins.get(to).is_some_and(|b| b.contains(contig_id)) {
+        return Err(DataError::DataAssertion(format!(
+            "Contig '{}' is already in bin '{}'.",
+            contig_id.0, to.0
+        )));
+    }
+
+    let source = bins
+        .get_mut(from)
+        .ok_or_else(|| DataError::NotFound(format!("Bin '{}'", from.0)))?;
+    let contig = source.remove_contig(contig_id).ok_or_else(|| {
+        DataError::NotFound(format!("Contig '{}' in bin '{}'", contig_id.0, from.0))
+    })?;
+
+    bins.entry(to.clone())
+        .or_insert_with(|| Bin::new(to.clone()))
+        .contig_metadata
+        .push(contig);
+
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct BinMetadataRecord {
     pub id: BinId,
@@ -98,34 +406,215 @@ pub struct BinMetadataRecord {
     pub completeness: Option<f64>,
     pub contamination: Option<f64>,
     pub quality: Option<BinQuality>,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Comma separated tags.
+    #[serde(default)]
+    pub tags: Option<String>,
+    #[serde(default)]
+    pub gc_content: Option<f64>,
+    #[serde(default)]
+    pub genome_size: Option<u64>,
+    #[serde(default)]
+    pub n50: Option<u64>,
+    #[serde(default)]
+    pub contig_count: Option<u64>,
+    #[serde(default)]
+    pub coding_density: Option<f64>,
+    #[serde(default)]
+    pub quality_notes: Option<String>,
 }
 
-impl BinMetadataRecord {}
+impl BinMetadataRecord {
+    pub fn join_tags(tags: &[String]) -> Option<String> {
+        if tags.is_empty() {
+            None
+        } else {
+            Some(tags.join(","))
+        }
+    }
 
-#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+    pub fn split_tags(tags: Option<&str>) -> Vec<String> {
+        tags.map(|t| {
+            t.split(',')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> BinStats {
+        BinStats {
+            gc_content: self.gc_content,
+            genome_size: self.genome_size,
+            n50: self.n50,
+            contig_count: self.contig_count,
+            coding_density: self.coding_density,
+            quality_notes: self.quality_notes.clone(),
+        }
+    }
+
+    pub fn contig_assignment(&self) -> ContigAssignment {
+        ContigAssignment {
+            contig_id: self.contig_id.clone(),
+            assignment: self.assignment,
+            note: self.note.clone().filter(|n| !n.is_empty()),
+            tags: Self::split_tags(self.tags.as_deref()),
+        }
+    }
+}
+
+/// MIMAG quality tier. `NC` (near-complete) bins meet the high quality
+/// completeness and contamination thresholds but not the rRNA and tRNA
+/// criteria, or are missing from the project's rRNA and tRNA counts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
 pub enum BinQuality {
     HQ,
+    NC,
     MQ,
     LQ,
 }
 
-impl BinQuality {
-    pub fn from_values(completenss: f64, contamination: f64) -> Self {
-        match (completenss, contamination) {
-            (comp, cont) if comp > 90.0 && cont < 5.0 => BinQuality::HQ,
-            (comp, cont) if comp > 50.0 && cont <= 10.0 => BinQuality::MQ,
-            _ => BinQuality::LQ,
+/// Number of rRNA genes of each type and of amino acids with a tRNA.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct RnaCounts {
+    pub rrna_5s: u32,
+    pub rrna_16s: u32,
+    pub rrna_23s: u32,
+    pub trnas: u32,
+}
+
+/// Completeness and contamination bounds of a tier. Contamination must be
+/// below `max_contamination`. Completeness must be above `min_completeness`,
+/// or equal to it if `completeness_inclusive` is set.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct QualityTier {
+    pub min_completeness: f64,
+    #[serde(default)]
+    pub completeness_inclusive: bool,
+    pub max_contamination: f64,
+}
+
+impl QualityTier {
+    fn contains(&self, completeness: f64, contamination: f64) -> bool {
+        let complete = if self.completeness_inclusive {
+            completeness >= self.min_completeness
+        } else {
+            completeness > self.min_completeness
+        };
+        complete && contamination < self.max_contamination
+    }
+}
+
+/// Thresholds used to assign `BinQuality`. The default follows MIMAG: more
+/// than 90% complete and less than 5% contaminated for high quality, at least
+/// 50% complete and less than 10% contaminated for medium quality.
+///
+/// Bins at exactly 10% contamination used to be medium quality and bins at
+/// exactly 50% completeness low quality. Projects are reassessed when they are
+/// loaded, so both move to the MIMAG tier.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+#[serde(default)]
+pub struct QualityProfile {
+    pub high: QualityTier,
+    pub medium: QualityTier,
+    /// Whether high quality bins need a 5S, a 16S and a 23S rRNA.
+    pub require_rrnas: bool,
+    /// Amino acids with a tRNA that high quality bins need, out of 20.
+    pub min_trnas: u32,
+}
+
+impl Default for QualityProfile {
+    fn default() -> Self {
+        Self {
+            high: QualityTier {
+                min_completeness: 90.0,
+                completeness_inclusive: false,
+                max_contamination: 5.0,
+            },
+            medium: QualityTier {
+                min_completeness: 50.0,
+                completeness_inclusive: true,
+                max_contamination: 10.0,
+            },
+            require_rrnas: true,
+            min_trnas: 18,
+        }
+    }
+}
+
+impl QualityProfile {
+    /// Whether high quality needs rRNA or tRNA counts. Projects without
+    /// counts are judged on completeness and contamination alone.
+    pub fn checks_rna(&self, has_rna_input: bool) -> bool {
+        has_rna_input && (self.require_rrnas || self.min_trnas > 0)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let tiers = [("high", self.high), ("medium", self.medium)];
+        for (name, tier) in tiers {
+            if !(0.0..=100.0).contains(&tier.min_completeness)
+                || !(0.0..=100.0).contains(&tier.max_contamination)
+            {
+                return Err(format!(
+                    "Thresholds of the {} quality tier must be percentages",
+                    name
+                ));
+            }
+        }
+        if self.high.min_completeness < self.medium.min_completeness
+            || self.high.max_contamination > self.medium.max_contamination
+        {
+            return Err("The high quality tier must be stricter than the medium tier".to_string());
+        }
+        if self.min_trnas > 20 {
+            return Err(format!(
+                "min_trnas is {} but there are only 20 amino acids",
+                self.min_trnas
+            ));
         }
+        Ok(())
+    }
+
+    pub fn assess(
+        &self,
+        completeness: f64,
+        contamination: f64,
+        rna: Option<&RnaCounts>,
+        has_rna_input: bool,
+    ) -> BinQuality {
+        if self.high.contains(completeness, contamination) {
+            if !self.checks_rna(has_rna_input) || rna.is_some_and(|rna| self.rna_complete(rna)) {
+                BinQuality::HQ
+            } else {
+                BinQuality::NC
+            }
+        } else if self.medium.contains(completeness, contamination) {
+            BinQuality::MQ
+        } else {
+            BinQuality::LQ
+        }
+    }
+
+    fn rna_complete(&self, rna: &RnaCounts) -> bool {
+        let rrnas = rna.rrna_5s > 0 && rna.rrna_16s > 0 && rna.rrna_23s > 0;
+        (rrnas || !self.require_rrnas) && rna.trnas >= self.min_trnas
     }
 }
 
-impl ToString for BinQuality {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for BinQuality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            BinQuality::HQ => "HQ".to_string(),
-            BinQuality::MQ => "MQ".to_string(),
-            BinQuality::LQ => "LQ".to_string(),
+            BinQuality::HQ => write!(f, "HQ"),
+            BinQuality::NC => write!(f, "NC"),
+            BinQuality::MQ => write!(f, "MQ"),
+            BinQuality::LQ => write!(f, "LQ"),
         }
     }
 }
@@ -136,9 +625,99 @@ impl FromStr for BinQuality {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "HQ" => Ok(BinQuality::HQ),
+            "NC" => Ok(BinQuality::NC),
             "MQ" => Ok(BinQuality::MQ),
             "LQ" => Ok(BinQuality::LQ),
             _ => Err(format!("Could not convert '{}' to BinQuality.", s)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RNA_COMPLETE: RnaCounts = RnaCounts {
+        rrna_5s: 1,
+        rrna_16s: 1,
+        rrna_23s: 2,
+        trnas: 18,
+    };
+
+    fn assess(completeness: f64, contamination: f64) -> BinQuality {
+        QualityProfile::default().assess(completeness, contamination, None, false)
+    }
+
+    #[test]
+    fn default_profile_completeness_bounds() {
+        assert_eq!(assess(90.0, 1.0), BinQuality::MQ);
+        assert_eq!(assess(90.01, 1.0), BinQuality::HQ);
+        assert_eq!(assess(50.0, 1.0), BinQuality::MQ);
+        assert_eq!(assess(49.99, 1.0), BinQuality::LQ);
+    }
+
+    #[test]
+    fn default_profile_contamination_bounds() {
+        assert_eq!(assess(95.0, 4.99), BinQuality::HQ);
+        assert_eq!(assess(95.0, 5.0), BinQuality::MQ);
+        assert_eq!(assess(60.0, 9.99), BinQuality::MQ);
+        assert_eq!(assess(60.0, 10.0), BinQuality::LQ);
+    }
+
+    #[test]
+    fn inclusive_high_completeness() {
+        let mut profile = QualityProfile::default();
+        profile.high.completeness_inclusive = true;
+
+        assert_eq!(profile.assess(90.0, 1.0, None, false), BinQuality::HQ);
+    }
+
+    #[test]
+    fn rna_is_only_checked_with_rna_input() {
+        let profile = QualityProfile::default();
+
+        assert_eq!(profile.assess(95.0, 1.0, None, false), BinQuality::HQ);
+        assert_eq!(profile.assess(95.0, 1.0, None, true), BinQuality::NC);
+        assert_eq!(
+            profile.assess(95.0, 1.0, Some(&RNA_COMPLETE), true),
+            BinQuality::HQ
+        );
+        // RNA never lifts a bin into high quality.
+        assert_eq!(
+            profile.assess(80.0, 1.0, Some(&RNA_COMPLETE), true),
+            BinQuality::MQ
+        );
+    }
+
+    #[test]
+    fn rna_requirements() {
+        let missing_5s = RnaCounts {
+            rrna_5s: 0,
+            ..RNA_COMPLETE
+        };
+        let few_trnas = RnaCounts {
+            trnas: 17,
+            ..RNA_COMPLETE
+        };
+        let mut profile = QualityProfile::default();
+
+        assert_eq!(
+            profile.assess(95.0, 1.0, Some(&missing_5s), true),
+            BinQuality::NC
+        );
+        assert_eq!(
+            profile.assess(95.0, 1.0, Some(&few_trnas), true),
+            BinQuality::NC
+        );
+
+        profile.require_rrnas = false;
+        assert_eq!(
+            profile.assess(95.0, 1.0, Some(&missing_5s), true),
+            BinQuality::HQ
+        );
+
+        profile.min_trnas = 0;
+        assert!(!profile.checks_rna(true));
+        assert_eq!(profile.assess(95.0, 1.0, None, true), BinQuality::HQ);
+    }
+}
@@ -0,0 +1,499 @@
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS, Default, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum Linkage {
+    Single,
+    Complete,
+    #[default]
+    Average,
+    Ward,
+}
+
+/// Distance between two observations. Cells that are `None` in either
+/// observation are skipped and the remaining distance is scaled up to the full
+/// number of dimensions. Pairs without any shared cells, or with fewer than
+/// two for `Correlation`, get the largest distance seen in the matrix.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS, Default, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum DistanceMetric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Correlation,
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, a: &[Option<f64>], b: &[Option<f64>]) -> Option<f64> {
+        let shared = a
+            .iter()
+            .zip(b.iter())
+            .filter_map(|(x, y)| x.zip(*y))
+            .collect::<Vec<(f64, f64)>>();
+
+        if shared.is_empty() {
+            return None;
+        }
+        let scale = a.len().max(b.len()) as f64 / shared.len() as f64;
+
+        match self {
+            DistanceMetric::Euclidean => {
+                let sum_square = shared.iter().map(|(x, y)| (x - y).powi(2)).sum::<f64>();
+                Some((sum_square * scale).sqrt())
+            }
+            DistanceMetric::Manhattan => {
+                let sum_abs = shared.iter().map(|(x, y)| (x - y).abs()).sum::<f64>();
+                Some(sum_abs * scale)
+            }
+            DistanceMetric::Correlation => {
+                if shared.len() < 2 {
+                    return None;
+                }
+                let n = shared.len() as f64;
+                let mean_x = shared.iter().map(|(x, _)| x).sum::<f64>() / n;
+                let mean_y = shared.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+                let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+                for (x, y) in &shared {
+                    cov += (x - mean_x) * (y - mean_y);
+                    var_x += (x - mean_x).powi(2);
+                    var_y += (y - mean_y).powi(2);
+                }
+                if var_x == 0.0 || var_y == 0.0 {
+                    // Flat profiles carry no correlation signal. Identical
+                    // profiles are still considered equal.
+                    let identical = shared.iter().all(|(x, y)| x == y);
+                    return Some(if identical { 0.0 } else { 1.0 });
+                }
+
+                Some(1.0 - cov / (var_x.sqrt() * var_y.sqrt()))
+            }
+        }
+    }
+}
+
+/// One merge step of the clustering. Leaves are numbered `0..n` and the
+/// cluster created by merge `i` is numbered `n + i`, as in scipy's linkage
+/// matrix.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct DendrogramNode {
+    pub left: usize,
+    pub right: usize,
+    pub distance: f64,
+    pub size: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct Dendrogram {
+    pub n_leaves: usize,
+    pub merges: Vec<DendrogramNode>,
+}
+
+impl Dendrogram {
+    /// Leaves in the order they appear when the dendrogram is drawn.
+    pub fn leaf_order(&self) -> Vec<usize> {
+        if self.merges.is_empty() {
+            return (0..self.n_leaves).collect();
+        }
+
+        let mut order = Vec::with_capacity(self.n_leaves);
+        let mut stack = vec![self.n_leaves + self.merges.len() - 1];
+        while let Some(node) = stack.pop() {
+            if node < self.n_leaves {
+                order.push(node);
+            } else {
+                let merge = &self.merges[node - self.n_leaves];
+                stack.push(merge.right);
+                stack.push(merge.left);
+            }
+        }
+        order
+    }
+
+    /// Renumber the leaves so that leaf `order[i]` becomes leaf `i`. Used when
+    /// the clustered axis is reordered by `leaf_order`.
+    pub fn relabel_leaves(self, order: &[usize]) -> Self {
+        let mut new_label = vec![0; self.n_leaves];
+        for (position, &leaf) in order.iter().enumerate() {
+            new_label[leaf] = position;
+        }
+        let relabel = |node: usize| {
+            if node < self.n_leaves {
+                new_label[node]
+            } else {
+                node
+            }
+        };
+
+        let merges = self
+            .merges
+            .iter()
+            .map(|m| DendrogramNode {
+                left: relabel(m.left),
+                right: relabel(m.right),
+                distance: m.distance,
+                size: m.size,
+            })
+            .collect();
+
+        Self {
+            n_leaves: self.n_leaves,
+            merges,
+        }
+    }
+}
+
+/// Condensed upper triangle of a pairwise distance matrix.
+struct CondensedMatrix {
+    n: usize,
+    values: Vec<f64>,
+}
+
+impl CondensedMatrix {
+    fn from_observations(observations: &[Vec<Option<f64>>], metric: DistanceMetric) -> Self {
+        let n = observations.len();
+        let mut values = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let d = metric
+                    .distance(&observations[i], &observations[j])
+                    .unwrap_or(f64::NAN);
+                values.push(d);
+            }
+        }
+
+        let max_distance = values
+            .iter()
+            .filter(|d| d.is_finite())
+            .cloned()
+            .fold(0.0, f64::max);
+        for d in values.iter_mut().filter(|d| !d.is_finite()) {
+            *d = max_distance;
+        }
+
+        Self { n, values }
+    }
+
+    fn index(&self, i: usize, j: usize) -> usize {
+        let (i, j) = if i < j { (i, j) } else { (j, i) };
+        self.n * i - i * (i + 1) / 2 + (j - i - 1)
+    }
+
+    fn get(&self, i: usize, j: usize) -> f64 {
+        self.values[self.index(i, j)]
+    }
+
+    fn set(&mut self, i: usize, j: usize, value: f64) {
+        let idx = self.index(i, j);
+        self.values[idx] = value;
+    }
+}
+
+impl Linkage {
+    /// Lance-Williams update of the distance between cluster `k` and the
+    /// cluster formed by merging `i` and `j`.
+    fn update(&self, d_ik: f64, d_jk: f64, d_ij: f64, n_i: usize, n_j: usize, n_k: usize) -> f64 {
+        let (n_i, n_j, n_k) = (n_i as f64, n_j as f64, n_k as f64);
+        match self {
+            Linkage::Single => d_ik.min(d_jk),
+            Linkage::Complete => d_ik.max(d_jk),
+            Linkage::Average => (n_i * d_ik + n_j * d_jk) / (n_i + n_j),
+            Linkage::Ward => {
+                let total = n_i + n_j + n_k;
+                (((n_i + n_k) * d_ik.powi(2) + (n_j + n_k) * d_jk.powi(2) - n_k * d_ij.powi(2))
+                    / total)
+                    .max(0.0)
+                    .sqrt()
+            }
+        }
+    }
+}
+
+/// Agglomerative clustering of `observations` (rows) using the
+/// nearest-neighbour chain algorithm.
+pub fn hierarchical_cluster(
+    observations: &[Vec<Option<f64>>],
+    metric: DistanceMetric,
+    linkage: Linkage,
+) -> Dendrogram {
+    let n = observations.len();
+    if n < 2 {
+        return Dendrogram {
+            n_leaves: n,
+            merges: Vec::new(),
+        };
+    }
+
+    let mut distances = CondensedMatrix::from_observations(observations, metric);
+    let mut sizes = vec![1usize; n];
+    let mut active = vec![true; n];
+    let mut chain: Vec<usize> = Vec::new();
+    let mut raw_merges: Vec<(usize, usize, f64)> = Vec::with_capacity(n - 1);
+
+    while raw_merges.len() < n - 1 {
+        if chain.is_empty() {
+            let first = active.iter().position(|a| *a).expect("active cluster");
+            chain.push(first);
+        }
+
+        let (x, y, d_xy) = loop {
+            let x = *chain.last().unwrap();
+            let previous = chain.len().checked_sub(2).map(|i| chain[i]);
+
+            let (mut y, mut min_dist) = match previous {
+                Some(p) => (p, distances.get(x, p)),
+                None => (usize::MAX, f64::INFINITY),
+            };
+            for i in (0..n).filter(|&i| active[i] && i != x) {
+                let d = distances.get(x, i);
+                if d < min_dist {
+                    min_dist = d;
+                    y = i;
+                }
+            }
+
+            if previous == Some(y) {
+                break (x, y, min_dist);
+            }
+            chain.push(y);
+        };
+        chain.truncate(chain.len() - 2);
+
+        let (x, y) = if x < y { (x, y) } else { (y, x) };
+        raw_merges.push((x, y, d_xy));
+
+        for k in (0..n).filter(|&k| active[k] && k != x && k != y) {
+            let updated = linkage.update(
+                distances.get(x, k),
+                distances.get(y, k),
+                d_xy,
+                sizes[x],
+                sizes[y],
+                sizes[k],
+            );
+            distances.set(y, k, updated);
+        }
+        active[x] = false;
+        sizes[y] += sizes[x];
+    }
+
+    // The chain algorithm finds merges out of order. Sort them by height and
+    // assign cluster labels afterwards.
+    raw_merges.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+    let mut parent: Vec<usize> = (0..(2 * n - 1)).collect();
+    let mut cluster_size = vec![1usize; 2 * n - 1];
+    let find = |parent: &mut Vec<usize>, mut node: usize| {
+        while parent[node] != node {
+            parent[node] = parent[parent[node]];
+            node = parent[node];
+        }
+        node
+    };
+
+    let merges = raw_merges
+        .into_iter()
+        .enumerate()
+        .map(|(step, (a, b, distance))| {
+            let root_a = find(&mut parent, a);
+            let root_b = find(&mut parent, b);
+            let (left, right) = if root_a < root_b {
+                (root_a, root_b)
+            } else {
+                (root_b, root_a)
+            };
+
+            let label = n + step;
+            parent[left] = label;
+            parent[right] = label;
+            cluster_size[label] = cluster_size[left] + cluster_size[right];
+
+            DendrogramNode {
+                left,
+                right,
+                distance,
+                size: cluster_size[label],
+            }
+        })
+        .collect();
+
+    Dendrogram {
+        n_leaves: n,
+        merges,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINKAGES: [Linkage; 4] = [
+        Linkage::Single,
+        Linkage::Complete,
+        Linkage::Average,
+        Linkage::Ward,
+    ];
+
+    fn points(values: &[f64]) -> Vec<Vec<Option<f64>>> {
+        values.iter().map(|v| vec![Some(*v)]).collect()
+    }
+
+    fn heights(dendrogram: &Dendrogram) -> Vec<f64> {
+        dendrogram.merges.iter().map(|m| m.distance).collect()
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn merge_heights_on_a_line() {
+        // Pairwise distances: ab 1, ac 3, ad 7, bc 2, bd 6, cd 4.
+        let observations = points(&[0.0, 1.0, 3.0, 7.0]);
+        let expected = [
+            (Linkage::Single, [1.0, 2.0, 4.0]),
+            (Linkage::Complete, [1.0, 3.0, 7.0]),
+            (Linkage::Average, [1.0, 2.5, 17.0 / 3.0]),
+            // sqrt(2 n_a n_b / (n_a + n_b)) times the distance of the centroids
+            (
+                Linkage::Ward,
+                [1.0, (25.0f64 / 3.0).sqrt(), 17.0 / 6.0f64.sqrt()],
+            ),
+        ];
+
+        for (linkage, heights_expected) in expected {
+            let dendrogram =
+                hierarchical_cluster(&observations, DistanceMetric::Euclidean, linkage);
+
+            assert_eq!(dendrogram.n_leaves, 4);
+            assert_close(&heights(&dendrogram), &heights_expected);
+            let topology: Vec<_> = dendrogram
+                .merges
+                .iter()
+                .map(|m| (m.left, m.right, m.size))
+                .collect();
+            assert_eq!(topology, [(0, 1, 2), (2, 4, 3), (3, 5, 4)], "{:?}", linkage);
+        }
+    }
+
+    #[test]
+    fn merge_heights_do_not_depend_on_input_order() {
+        for linkage in LINKAGES {
+            let sorted = hierarchical_cluster(
+                &points(&[0.0, 1.0, 3.0, 7.0]),
+                DistanceMetric::Euclidean,
+                linkage,
+            );
+            let shuffled = hierarchical_cluster(
+                &points(&[7.0, 0.0, 3.0, 1.0]),
+                DistanceMetric::Euclidean,
+                linkage,
+            );
+            assert_close(&heights(&shuffled), &heights(&sorted));
+        }
+    }
+
+    #[test]
+    fn fewer_than_two_observations() {
+        for observations in [vec![], points(&[1.0])] {
+            let dendrogram =
+                hierarchical_cluster(&observations, DistanceMetric::Euclidean, Linkage::Average);
+
+            assert_eq!(dendrogram.n_leaves, observations.len());
+            assert!(dendrogram.merges.is_empty());
+            assert_eq!(
+                dendrogram.leaf_order(),
+                (0..observations.len()).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn missing_cells_are_scaled_and_unshared_pairs_get_the_max() {
+        let observations = vec![
+            vec![Some(0.0), None],
+            vec![None, Some(1.0)],
+            vec![Some(3.0), None],
+        ];
+        let shared = (9.0f64 * 2.0).sqrt();
+
+        assert_eq!(
+            DistanceMetric::Euclidean.distance(&observations[0], &observations[1]),
+            None
+        );
+        let matrix = CondensedMatrix::from_observations(&observations, DistanceMetric::Euclidean);
+        assert_close(&matrix.values, &[shared, shared, shared]);
+        assert_close(&[matrix.get(0, 2)], &[shared]);
+        assert_eq!(
+            DistanceMetric::Manhattan.distance(&observations[0], &observations[2]),
+            Some(6.0)
+        );
+    }
+
+    #[test]
+    fn all_pairs_unshared() {
+        let observations = vec![vec![Some(0.0), None], vec![None, Some(1.0)]];
+        let matrix = CondensedMatrix::from_observations(&observations, DistanceMetric::Euclidean);
+
+        assert_eq!(matrix.values, [0.0]);
+    }
+
+    #[test]
+    fn correlation_needs_two_shared_cells() {
+        let a = [Some(0.1), Some(0.9), None];
+        let b = [Some(0.1), None, Some(0.5)];
+        assert_eq!(DistanceMetric::Correlation.distance(&a, &b), None);
+
+        let up = [Some(0.1), Some(0.5), Some(0.9)];
+        let scaled = [Some(0.2), Some(0.6), Some(1.0)];
+        let down = [Some(0.9), Some(0.5), Some(0.1)];
+        assert_close(
+            &[
+                DistanceMetric::Correlation.distance(&up, &scaled).unwrap(),
+                DistanceMetric::Correlation.distance(&up, &down).unwrap(),
+            ],
+            &[0.0, 2.0],
+        );
+    }
+
+    #[test]
+    fn relabelled_leaves_follow_the_leaf_order() {
+        let observations = points(&[7.0, 0.0, 3.0, 1.0, 6.5]);
+        for linkage in LINKAGES {
+            let dendrogram =
+                hierarchical_cluster(&observations, DistanceMetric::Euclidean, linkage);
+            let order = dendrogram.leaf_order();
+
+            let mut sorted = order.clone();
+            sorted.sort();
+            assert_eq!(sorted, [0, 1, 2, 3, 4]);
+
+            let relabelled = dendrogram.clone().relabel_leaves(&order);
+            assert_eq!(relabelled.leaf_order(), [0, 1, 2, 3, 4]);
+            assert_eq!(heights(&relabelled), heights(&dendrogram));
+
+            // Observations reordered by the leaf order keep their neighbours.
+            let reordered: Vec<_> = order.iter().map(|&i| observations[i].clone()).collect();
+            for (merge, relabelled) in dendrogram.merges.iter().zip(&relabelled.merges) {
+                if merge.left < 5 {
+                    assert_eq!(observations[merge.left], reordered[relabelled.left]);
+                }
+                if merge.right < 5 {
+                    assert_eq!(observations[merge.right], reordered[relabelled.right]);
+                }
+            }
+        }
+    }
+}
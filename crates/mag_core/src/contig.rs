@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
 
 use epimetheus_methylome::Motif;
 use serde::{Deserialize, Serialize};
@@ -12,9 +12,15 @@ pub struct ContigId(pub String);
 #[derive(Clone)]
 pub struct Contig {
     pub contig_id: ContigId,
-    pub motifs: HashMap<Motif, MotifSignature>,
+    pub motifs: HashMap<Arc<Motif>, MotifSignature>,
     pub mean_coverage: f64,
-    // length could be nice
+    /// Set when the project has an assembly.
+    pub sequence: Option<SequenceStats>,
+    /// Mean depth per sample, in the order of `Project::depth_samples`. Set
+    /// when the project has a depth table.
+    pub sample_depths: Option<Vec<f64>>,
+    /// Label from a contig classifier such as Kraken2.
+    pub taxonomy: Option<String>,
 }
 
 impl Contig {
@@ -24,6 +30,44 @@ impl Contig {
     }
 }
 
+/// Attributes of a contig sequence from the assembly.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct SequenceStats {
+    #[ts(type = "number")]
+    pub length: u64,
+    /// Fraction of G and C among the called bases, as in CheckM2.
+    pub gc_content: f64,
+    #[ts(type = "number")]
+    pub n_count: u64,
+}
+
+impl SequenceStats {
+    pub fn from_sequence(sequence: &[u8]) -> Self {
+        let mut gc = 0u64;
+        let mut n_count = 0u64;
+        for base in sequence {
+            match base.to_ascii_uppercase() {
+                b'G' | b'C' => gc += 1,
+                b'N' => n_count += 1,
+                _ => {}
+            }
+        }
+
+        let length = sequence.len() as u64;
+        let called = length - n_count;
+        Self {
+            length,
+            gc_content: if called == 0 {
+                0.0
+            } else {
+                gc as f64 / called as f64
+            },
+            n_count,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, TS, Default, PartialEq, Eq, Copy)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
 pub enum Assignment {
@@ -34,11 +78,43 @@ pub enum Assignment {
     Ambiguous,
 }
 
+impl std::fmt::Display for Assignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Assignment::None => write!(f, "None"),
+            Assignment::Clean => write!(f, "Clean"),
+            Assignment::Contamination => write!(f, "Contamination"),
+            Assignment::Ambiguous => write!(f, "Ambiguous"),
+        }
+    }
+}
+
+impl FromStr for Assignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "None" => Ok(Assignment::None),
+            "Clean" => Ok(Assignment::Clean),
+            "Contamination" => Ok(Assignment::Contamination),
+            "Ambiguous" => Ok(Assignment::Ambiguous),
+            _ => Err(format!("Could not convert '{}' to Assignment.", s)),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, TS, PartialEq, Eq)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
 pub struct ContigAssignment {
     pub contig_id: ContigId,
     pub assignment: Assignment,
+    /// Free text explaining the assignment.
+    #[serde(default)]
+    #[ts(optional)]
+    pub note: Option<String>,
+    /// Tags can't contain ',' since they are saved comma separated.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl ContigAssignment {
@@ -46,6 +122,16 @@ impl ContigAssignment {
         Self {
             contig_id,
             assignment,
+            note: None,
+            tags: Vec::new(),
         }
     }
+
+    /// Tag that can't be saved and read back as is, if any.
+    pub fn invalid_tag(&self) -> Option<&str> {
+        self.tags
+            .iter()
+            .map(String::as_str)
+            .find(|t| t.contains(',') || t.trim().is_empty() || t.trim() != *t)
+    }
 }
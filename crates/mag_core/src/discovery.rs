@@ -0,0 +1,105 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{errors::DataError, project::ProjectDetails};
+
+const PROJECT_FILE: &str = "project.toml";
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct InputFileStatus {
+    pub name: String,
+    pub path: PathBuf,
+    pub exists: bool,
+}
+
+/// A `project.toml` found on disk. `details` is `None` and `error` is set if
+/// the file could not be parsed.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct DiscoveredProject {
+    pub path: PathBuf,
+    pub details: Option<ProjectDetails>,
+    pub error: Option<String>,
+    pub inputs: Vec<InputFileStatus>,
+}
+
+impl DiscoveredProject {
+    pub fn from_toml(path: PathBuf) -> Self {
+        match ProjectDetails::from_toml(&path) {
+            Ok(details) => {
+                let inputs = details
+                    .input_files()
+                    .into_iter()
+                    .map(|(name, input)| InputFileStatus {
+                        name: name.to_string(),
+                        path: input.to_path_buf(),
+                        exists: input.is_file(),
+                    })
+                    .collect();
+                Self {
+                    path,
+                    details: Some(details),
+                    error: None,
+                    inputs,
+                }
+            }
+            Err(e) => Self {
+                path,
+                details: None,
+                error: Some(e.to_string()),
+                inputs: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Find every `project.toml` below `root`, sorted by path. Hidden directories
+/// and symlinked directories are skipped, and directories that cannot be read
+/// are logged and skipped.
+pub fn discover_projects(root: &Path) -> Result<Vec<DiscoveredProject>, DataError> {
+    if !root.is_dir() {
+        return Err(DataError::NotFound(format!(
+            "Projects directory '{}'",
+            root.display()
+        )));
+    }
+
+    let mut found = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Skipping '{}': {}", dir.display(), e);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !entry.file_name().to_string_lossy().starts_with('.') {
+                    pending.push(path);
+                }
+            } else if entry.file_name() == PROJECT_FILE {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+
+    tracing::info!("Found {} projects under {}", found.len(), root.display());
+    Ok(found
+        .into_iter()
+        .map(DiscoveredProject::from_toml)
+        .collect())
+}
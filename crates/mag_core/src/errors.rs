@@ -8,6 +8,9 @@ pub enum DataError {
     #[error("Read error: {0}")]
     Read(#[from] csv::Error),
 
+    #[error("Project store error: {0}")]
+    Store(#[from] rusqlite::Error),
+
     #[error("Data not found: {0}")]
     NotFound(String),
 
@@ -16,4 +19,13 @@ pub enum DataError {
 
     #[error("Bin Quality error: {0}")]
     BinQuality(String),
+
+    #[error("Project file error: {0}")]
+    ProjectFile(String),
+
+    #[error("MetadataUpdate error: {0}")]
+    MetadataUpdate(String),
+
+    #[error("Loading was cancelled")]
+    Cancelled,
 }
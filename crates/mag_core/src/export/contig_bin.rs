@@ -0,0 +1,79 @@
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    bin::{Bin, BinId},
+    errors::DataError,
+    export::AmbiguousPolicy,
+    io::reader::contig_bin::ContigBinRecord,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigBinExport {
+    pub path: PathBuf,
+    pub n_bins: usize,
+    pub n_contigs: usize,
+}
+
+/// Write the refined binning as a `contig\tbin` table that `ContigBinReader`
+/// can read back.
+pub fn write_contig_bin<W: Write>(
+    bins: &BTreeMap<BinId, Bin>,
+    writer: W,
+    policy: AmbiguousPolicy,
+) -> Result<(usize, usize), DataError> {
+    let mut wtr = csv::WriterBuilder::new()
+        .has_headers(true)
+        .delimiter(b'\t')
+        .from_writer(writer);
+
+    let mut n_bins = 0;
+    let mut n_contigs = 0;
+    for bin in bins.values() {
+        let kept = bin
+            .contig_metadata
+            .iter()
+            .filter(|c| policy.keep(c.assignment))
+            .collect::<Vec<_>>();
+        if kept.is_empty() {
+            continue;
+        }
+
+        for contig in kept {
+            wtr.serialize(ContigBinRecord {
+                contig: contig.contig_id.0.clone(),
+                bin: bin.id.0.clone(),
+            })?;
+            n_contigs += 1;
+        }
+        n_bins += 1;
+    }
+    wtr.flush()?;
+
+    Ok((n_bins, n_contigs))
+}
+
+pub fn export_contig_bin(
+    bins: &BTreeMap<BinId, Bin>,
+    path: &Path,
+    policy: AmbiguousPolicy,
+) -> Result<ContigBinExport, DataError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = std::fs::File::create(path)?;
+    let (n_bins, n_contigs) = write_contig_bin(bins, file, policy)?;
+
+    Ok(ContigBinExport {
+        path: path.to_path_buf(),
+        n_bins,
+        n_contigs,
+    })
+}
@@ -0,0 +1,138 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    bin::{Bin, BinId},
+    contig::ContigId,
+    errors::DataError,
+    export::AmbiguousPolicy,
+    io::{reader::fasta::FastaReader, writer::fasta::FastaWriter},
+};
+
+/// Upper bound on simultaneously open output files. Bins beyond this are
+/// written in additional passes over the assembly.
+const MAX_OPEN_FILES: usize = 128;
+
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct BinFastaExport {
+    pub bin_id: BinId,
+    pub path: Option<PathBuf>,
+    pub n_contigs: usize,
+    pub missing_contigs: Vec<ContigId>,
+}
+
+/// Write one `<bin>.fa` per bin to `outdir` with the sequences of the contigs
+/// kept under `policy`. Bins without any kept contigs are not written.
+pub fn export_bins_fasta(
+    assembly: &Path,
+    bins: &BTreeMap<BinId, Bin>,
+    outdir: &Path,
+    policy: AmbiguousPolicy,
+) -> Result<Vec<BinFastaExport>, DataError> {
+    std::fs::create_dir_all(outdir)?;
+
+    let kept: BTreeMap<&BinId, HashSet<&ContigId>> = bins
+        .iter()
+        .map(|(bin_id, bin)| {
+            let contigs = bin
+                .contig_metadata
+                .iter()
+                .filter(|c| policy.keep(c.assignment))
+                .map(|c| &c.contig_id)
+                .collect();
+            (bin_id, contigs)
+        })
+        .collect();
+
+    let non_empty: Vec<&BinId> = kept
+        .iter()
+        .filter(|(_, contigs)| !contigs.is_empty())
+        .map(|(bin_id, _)| *bin_id)
+        .collect();
+
+    // Bin ids come from user data, so check them all before writing anything.
+    let unsafe_ids: Vec<&str> = non_empty
+        .iter()
+        .filter(|bin_id| !is_safe_file_stem(&bin_id.0))
+        .map(|bin_id| bin_id.0.as_str())
+        .collect();
+    if !unsafe_ids.is_empty() {
+        return Err(DataError::DataAssertion(format!(
+            "Can't export bins whose ids are not valid file names: '{}'",
+            unsafe_ids.join("', '")
+        )));
+    }
+
+    let mut written: BTreeMap<&BinId, HashSet<ContigId>> = BTreeMap::new();
+    for batch in non_empty.chunks(MAX_OPEN_FILES) {
+        let mut contig_bins: HashMap<&ContigId, Vec<&BinId>> = HashMap::new();
+        let mut writers = BTreeMap::new();
+        for &bin_id in batch {
+            for &contig_id in &kept[bin_id] {
+                contig_bins.entry(contig_id).or_default().push(bin_id);
+            }
+            writers.insert(bin_id, FastaWriter::new(&bin_fasta_path(outdir, bin_id))?);
+        }
+
+        let mut rdr = FastaReader::new(assembly)?;
+        for record in rdr.records() {
+            let record = record?;
+            let contig_id = ContigId(record.id.clone());
+            let Some(targets) = contig_bins.get(&contig_id) else {
+                continue;
+            };
+
+            for &bin_id in targets {
+                writers
+                    .get_mut(bin_id)
+                    .expect("writer opened for every bin in batch")
+                    .write_record(&record)?;
+                written.entry(bin_id).or_default().insert(contig_id.clone());
+            }
+        }
+
+        for (bin_id, writer) in writers.iter_mut() {
+            writer.flush()?;
+            if !written.contains_key(bin_id) {
+                std::fs::remove_file(bin_fasta_path(outdir, bin_id))?;
+            }
+        }
+    }
+
+    let summary = kept
+        .into_iter()
+        .map(|(bin_id, contigs)| {
+            let bin_written = written.get(bin_id);
+            let mut missing_contigs: Vec<ContigId> = contigs
+                .into_iter()
+                .filter(|c| !bin_written.is_some_and(|w| w.contains(*c)))
+                .cloned()
+                .collect();
+            missing_contigs.sort_by(|a, b| a.0.cmp(&b.0));
+
+            BinFastaExport {
+                bin_id: bin_id.clone(),
+                path: bin_written.map(|_| bin_fasta_path(outdir, bin_id)),
+                n_contigs: bin_written.map_or(0, |w| w.len()),
+                missing_contigs,
+            }
+        })
+        .collect();
+
+    Ok(summary)
+}
+
+/// Whether `name` stays inside the directory it is joined to.
+fn is_safe_file_stem(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\', '\0']) && !name.contains("..") && name != "."
+}
+
+fn bin_fasta_path(outdir: &Path, bin_id: &BinId) -> PathBuf {
+    outdir.join(format!("{}.fa", bin_id.0))
+}
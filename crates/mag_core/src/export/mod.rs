@@ -0,0 +1,27 @@
+pub mod contig_bin;
+pub mod fasta;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::contig::Assignment;
+
+/// What to do with contigs marked `Ambiguous` when exporting refined bins.
+/// Contigs marked `Contamination` are always left out.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS, Default, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum AmbiguousPolicy {
+    #[default]
+    Keep,
+    Drop,
+}
+
+impl AmbiguousPolicy {
+    pub fn keep(&self, assignment: Assignment) -> bool {
+        match assignment {
+            Assignment::Contamination => false,
+            Assignment::Ambiguous => *self == AmbiguousPolicy::Keep,
+            Assignment::None | Assignment::Clean => true,
+        }
+    }
+}
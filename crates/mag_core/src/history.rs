@@ -0,0 +1,659 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    bin::{Bin, BinId},
+    contig::{ContigAssignment, ContigId},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum HistoryAction {
+    CreateBin,
+    UpdateMetadata,
+    SetAssignment,
+    MoveContig,
+    ResolveConflict,
+    RecruitContig,
+}
+
+impl std::fmt::Display for HistoryAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryAction::CreateBin => write!(f, "CreateBin"),
+            HistoryAction::UpdateMetadata => write!(f, "UpdateMetadata"),
+            HistoryAction::SetAssignment => write!(f, "SetAssignment"),
+            HistoryAction::MoveContig => write!(f, "MoveContig"),
+            HistoryAction::ResolveConflict => write!(f, "ResolveConflict"),
+            HistoryAction::RecruitContig => write!(f, "RecruitContig"),
+        }
+    }
+}
+
+impl std::str::FromStr for HistoryAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "CreateBin" => Ok(HistoryAction::CreateBin),
+            "UpdateMetadata" => Ok(HistoryAction::UpdateMetadata),
+            "SetAssignment" => Ok(HistoryAction::SetAssignment),
+            "MoveContig" => Ok(HistoryAction::MoveContig),
+            "ResolveConflict" => Ok(HistoryAction::ResolveConflict),
+            "RecruitContig" => Ok(HistoryAction::RecruitContig),
+            _ => Err(format!("Could not convert '{}' to HistoryAction.", s)),
+        }
+    }
+}
+
+/// A contig at its position in a bin.
+#[derive(Serialize, Deserialize, Debug, Clone, TS, PartialEq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct PlacedContig {
+    pub position: usize,
+    pub contig: ContigAssignment,
+}
+
+/// A contig that was added, removed or edited. `None` means the contig was
+/// not in the bin.
+#[derive(Serialize, Deserialize, Debug, Clone, TS, PartialEq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigChange {
+    pub contig_id: ContigId,
+    pub before: Option<PlacedContig>,
+    pub after: Option<PlacedContig>,
+}
+
+impl ContigChange {
+    /// The state to undo and the state to restore when applying the change
+    /// forward or backward.
+    fn sides(&self, forward: bool) -> (&Option<PlacedContig>, &Option<PlacedContig>) {
+        if forward {
+            (&self.before, &self.after)
+        } else {
+            (&self.after, &self.before)
+        }
+    }
+}
+
+/// Contigs of a bin that differ before and after an event. Contigs that are
+/// left as they were, in the same order, are not recorded.
+#[derive(Serialize, Deserialize, Debug, Clone, TS, PartialEq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct BinChange {
+    pub bin: BinId,
+    /// Whether the bin existed before and after the event.
+    pub existed_before: bool,
+    pub exists_after: bool,
+    pub contigs: Vec<ContigChange>,
+}
+
+/// A bin while a change to it is being applied.
+pub struct PendingBinChange {
+    bin: BinId,
+    before: Option<Vec<ContigAssignment>>,
+}
+
+impl BinChange {
+    /// Remember `bin` to be compared with `finish` once the change has been
+    /// applied.
+    pub fn start(bins: &BTreeMap<BinId, Bin>, bin: &BinId) -> PendingBinChange {
+        PendingBinChange {
+            bin: bin.clone(),
+            before: bins.get(bin).map(|b| b.contig_metadata.clone()),
+        }
+    }
+
+    /// Difference between two states of `bin`. `None` means the bin did not
+    /// exist.
+    pub fn between(
+        bin: BinId,
+        before: Option<&[ContigAssignment]>,
+        after: Option<&[ContigAssignment]>,
+    ) -> Self {
+        let old = before.unwrap_or_default();
+        let new = after.unwrap_or_default();
+        let old_index: HashMap<&ContigId, usize> = old
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (&c.contig_id, i))
+            .collect();
+        let new_index: HashMap<&ContigId, usize> = new
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (&c.contig_id, i))
+            .collect();
+
+        // Contigs that are unchanged can be left out as long as they keep
+        // their order, since restoring reinserts the others by position.
+        let same = |c: &ContigAssignment| new_index.get(&c.contig_id).map(|&i| &new[i]) == Some(c);
+        let kept_old: Vec<&ContigId> = old
+            .iter()
+            .filter(|c| same(c))
+            .map(|c| &c.contig_id)
+            .collect();
+        let kept_new: Vec<&ContigId> = new
+            .iter()
+            .filter(|c| old_index.get(&c.contig_id).map(|&i| &old[i]) == Some(*c))
+            .map(|c| &c.contig_id)
+            .collect();
+        let reordered = kept_old != kept_new;
+        let recorded = |id: &ContigId| {
+            reordered || old_index.get(id).map(|&i| &old[i]) != new_index.get(id).map(|&i| &new[i])
+        };
+
+        let placed = |contigs: &[ContigAssignment], index: &HashMap<&ContigId, usize>, id| {
+            index.get(id).map(|&position| PlacedContig {
+                position,
+                contig: contigs[position].clone(),
+            })
+        };
+        let contigs = old
+            .iter()
+            .map(|c| &c.contig_id)
+            .chain(
+                new.iter()
+                    .map(|c| &c.contig_id)
+                    .filter(|id| !old_index.contains_key(id)),
+            )
+            .filter(|id| recorded(id))
+            .map(|id| ContigChange {
+                contig_id: id.clone(),
+                before: placed(old, &old_index, id),
+                after: placed(new, &new_index, id),
+            })
+            .collect();
+
+        Self {
+            bin,
+            existed_before: before.is_some(),
+            exists_after: after.is_some(),
+            contigs,
+        }
+    }
+
+    pub fn is_noop(&self) -> bool {
+        self.existed_before == self.exists_after && self.contigs.is_empty()
+    }
+
+    /// Bring the bin to its state before (`forward` false) or after the
+    /// event, starting from the other state.
+    fn apply(&self, bins: &mut BTreeMap<BinId, Bin>, forward: bool) {
+        let exists = if forward {
+            self.exists_after
+        } else {
+            self.existed_before
+        };
+        if !exists {
+            bins.remove(&self.bin);
+            return;
+        }
+
+        let contigs = &mut bins
+            .entry(self.bin.clone())
+            .or_insert_with(|| Bin::new(self.bin.clone()))
+            .contig_metadata;
+
+        let remove: HashSet<&ContigId> = self
+            .contigs
+            .iter()
+            .filter(|c| c.sides(forward).0.is_some())
+            .map(|c| &c.contig_id)
+            .collect();
+        contigs.retain(|c| !remove.contains(&c.contig_id));
+
+        let mut insert: Vec<&PlacedContig> = self
+            .contigs
+            .iter()
+            .filter_map(|c| c.sides(forward).1.as_ref())
+            .collect();
+        insert.sort_by_key(|p| p.position);
+        for placed in insert {
+            let position = placed.position.min(contigs.len());
+            contigs.insert(position, placed.contig.clone());
+        }
+    }
+}
+
+impl PendingBinChange {
+    pub fn finish(self, bins: &BTreeMap<BinId, Bin>) -> BinChange {
+        BinChange::between(
+            self.bin.clone(),
+            self.before.as_deref(),
+            bins.get(&self.bin).map(|b| b.contig_metadata.as_slice()),
+        )
+    }
+}
+
+/// A recorded curation change. Undone events stay in the history until a new
+/// change is recorded, at which point they can no longer be redone and are
+/// dropped.
+#[derive(Serialize, Deserialize, Debug, Clone, TS, PartialEq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct HistoryEvent {
+    pub id: u64,
+    /// Milliseconds since the unix epoch.
+    #[ts(type = "number")]
+    pub timestamp: u64,
+    pub user: Option<String>,
+    pub action: HistoryAction,
+    pub description: String,
+    pub changes: Vec<BinChange>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    events: Vec<HistoryEvent>,
+}
+
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl History {
+    pub fn from_events(mut events: Vec<HistoryEvent>) -> Self {
+        events.sort_by_key(|e| e.id);
+        Self { events }
+    }
+
+    pub fn events(&self) -> &[HistoryEvent] {
+        &self.events
+    }
+
+    /// Record a change. Changes that left every bin untouched are ignored.
+    pub fn record(
+        &mut self,
+        action: HistoryAction,
+        user: Option<String>,
+        description: String,
+        changes: Vec<BinChange>,
+    ) -> Option<&HistoryEvent> {
+        let changes = changes
+            .into_iter()
+            .filter(|c| !c.is_noop())
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return None;
+        }
+
+        self.events.retain(|e| !e.undone);
+        let id = self.events.last().map(|e| e.id + 1).unwrap_or(1);
+        tracing::debug!("Recording history event {}: {}", id, description);
+
+        self.events.push(HistoryEvent {
+            id,
+            timestamp: now_millis(),
+            user,
+            action,
+            description,
+            changes,
+            undone: false,
+        });
+        self.events.last()
+    }
+
+    /// Undo the most recent change that has not been undone.
+    pub fn undo(&mut self, bins: &mut BTreeMap<BinId, Bin>) -> Option<HistoryEvent> {
+        let event = self.events.iter_mut().rev().find(|e| !e.undone)?;
+        for change in event.changes.iter().rev() {
+            change.apply(bins, false);
+        }
+        event.undone = true;
+
+        Some(event.clone())
+    }
+
+    /// Redo the oldest undone change.
+    pub fn redo(&mut self, bins: &mut BTreeMap<BinId, Bin>) -> Option<HistoryEvent> {
+        let event = self.events.iter_mut().find(|e| e.undone)?;
+        for change in &event.changes {
+            change.apply(bins, true);
+        }
+        event.undone = false;
+
+        Some(event.clone())
+    }
+
+    /// Undo every change made after `timestamp`. The reverted changes can be
+    /// redone until a new change is recorded.
+    pub fn revert_to(
+        &mut self,
+        bins: &mut BTreeMap<BinId, Bin>,
+        timestamp: u64,
+    ) -> Vec<HistoryEvent> {
+        let mut reverted = Vec::new();
+        while self
+            .events
+            .iter()
+            .rev()
+            .find(|e| !e.undone)
+            .is_some_and(|e| e.timestamp > timestamp)
+        {
+            match self.undo(bins) {
+                Some(event) => reverted.push(event),
+                None => break,
+            }
+        }
+        reverted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bin::move_contig, contig::Assignment, store::ProjectStore};
+
+    type Members = BTreeMap<BinId, Vec<ContigAssignment>>;
+
+    fn contig(id: &str) -> ContigAssignment {
+        ContigAssignment::new(ContigId(id.to_string()), Assignment::None)
+    }
+
+    fn bin_id(id: &str) -> BinId {
+        BinId(id.to_string())
+    }
+
+    fn bins(members: &[(&str, &[&str])]) -> BTreeMap<BinId, Bin> {
+        members
+            .iter()
+            .map(|(id, contigs)| {
+                let bin = Bin {
+                    contig_metadata: contigs.iter().map(|c| contig(c)).collect(),
+                    ..Bin::new(bin_id(id))
+                };
+                (bin_id(id), bin)
+            })
+            .collect()
+    }
+
+    fn members(bins: &BTreeMap<BinId, Bin>) -> Members {
+        bins.iter()
+            .map(|(id, bin)| (id.clone(), bin.contig_metadata.clone()))
+            .collect()
+    }
+
+    /// Apply `edit` to `bins` and record the change to `touched` as one event.
+    fn record(
+        history: &mut History,
+        bins: &mut BTreeMap<BinId, Bin>,
+        touched: &[&str],
+        action: HistoryAction,
+        edit: impl FnOnce(&mut BTreeMap<BinId, Bin>),
+    ) -> u64 {
+        let pending: Vec<PendingBinChange> = touched
+            .iter()
+            .map(|id| BinChange::start(bins, &bin_id(id)))
+            .collect();
+        edit(bins);
+        let changes = pending.into_iter().map(|c| c.finish(bins)).collect();
+        history
+            .record(action, None, format!("{:?}", action), changes)
+            .expect("change was recorded")
+            .id
+    }
+
+    /// Undo and redo the only event and check both states are restored.
+    fn assert_round_trip(
+        mut bins: BTreeMap<BinId, Bin>,
+        touched: &[&str],
+        action: HistoryAction,
+        edit: impl FnOnce(&mut BTreeMap<BinId, Bin>),
+    ) {
+        let mut history = History::default();
+        let before = members(&bins);
+        record(&mut history, &mut bins, touched, action, edit);
+        let after = members(&bins);
+        assert_ne!(before, after);
+
+        history.undo(&mut bins).unwrap();
+        assert_eq!(members(&bins), before);
+        history.redo(&mut bins).unwrap();
+        assert_eq!(members(&bins), after);
+        assert!(history.redo(&mut bins).is_none());
+    }
+
+    #[test]
+    fn move_round_trip() {
+        let mut bins = bins(&[("a", &["c1", "c2", "c3"]), ("b", &["c4"])]);
+        let moved = &mut bins.get_mut(&bin_id("a")).unwrap().contig_metadata[1];
+        moved.assignment = Assignment::Contamination;
+        moved.note = Some("GC outlier".to_string());
+        moved.tags = vec!["plasmid".to_string()];
+
+        assert_round_trip(bins, &["a", "b"], HistoryAction::MoveContig, |bins| {
+            move_contig(
+                bins,
+                &ContigId("c2".to_string()),
+                &bin_id("a"),
+                &bin_id("b"),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn move_into_new_bin_round_trip() {
+        let bins = bins(&[("a", &["c1", "c2"])]);
+
+        assert_round_trip(bins, &["a", "new"], HistoryAction::MoveContig, |bins| {
+            move_contig(
+                bins,
+                &ContigId("c1".to_string()),
+                &bin_id("a"),
+                &bin_id("new"),
+            )
+            .unwrap();
+        });
+    }
+
+    #[test]
+    fn recruit_round_trip() {
+        let bins = bins(&[("a", &["c1", "c2"])]);
+
+        assert_round_trip(bins, &["a"], HistoryAction::RecruitContig, |bins| {
+            bins.get_mut(&bin_id("a"))
+                .unwrap()
+                .contig_metadata
+                .push(contig("c9"));
+        });
+    }
+
+    #[test]
+    fn create_bin_round_trip() {
+        let bins = bins(&[("a", &["c1"])]);
+
+        assert_round_trip(bins, &["b"], HistoryAction::CreateBin, |bins| {
+            bins.insert(
+                bin_id("b"),
+                Bin {
+                    contig_metadata: vec![contig("c2"), contig("c3")],
+                    ..Bin::new(bin_id("b"))
+                },
+            );
+        });
+    }
+
+    #[test]
+    fn reorder_and_edit_round_trip() {
+        let bins = bins(&[("a", &["c1", "c2", "c3", "c4"])]);
+
+        assert_round_trip(bins, &["a"], HistoryAction::UpdateMetadata, |bins| {
+            let contigs = &mut bins.get_mut(&bin_id("a")).unwrap().contig_metadata;
+            contigs.swap(0, 3);
+            contigs[1].assignment = Assignment::Clean;
+            contigs.remove(2);
+        });
+    }
+
+    #[test]
+    fn unchanged_bins_are_not_recorded() {
+        let mut bins = bins(&[("a", &["c1"])]);
+        let pending = BinChange::start(&bins, &bin_id("a"));
+        let mut history = History::default();
+
+        let event = history.record(
+            HistoryAction::UpdateMetadata,
+            None,
+            "nothing".to_string(),
+            vec![pending.finish(&bins)],
+        );
+        assert!(event.is_none());
+        assert!(history.undo(&mut bins).is_none());
+    }
+
+    /// Three events on one bin and the state after each of them.
+    fn three_events() -> (History, BTreeMap<BinId, Bin>, Vec<Members>) {
+        let mut history = History::default();
+        let mut bins = bins(&[("a", &["c1", "c2"]), ("b", &["c3"])]);
+        let mut states = vec![members(&bins)];
+
+        record(
+            &mut history,
+            &mut bins,
+            &["a", "b"],
+            HistoryAction::MoveContig,
+            |bins| {
+                move_contig(
+                    bins,
+                    &ContigId("c1".to_string()),
+                    &bin_id("a"),
+                    &bin_id("b"),
+                )
+                .unwrap();
+            },
+        );
+        states.push(members(&bins));
+        record(
+            &mut history,
+            &mut bins,
+            &["a"],
+            HistoryAction::RecruitContig,
+            |bins| {
+                bins.get_mut(&bin_id("a"))
+                    .unwrap()
+                    .contig_metadata
+                    .push(contig("c4"));
+            },
+        );
+        states.push(members(&bins));
+        record(
+            &mut history,
+            &mut bins,
+            &["b"],
+            HistoryAction::SetAssignment,
+            |bins| {
+                bins.get_mut(&bin_id("b")).unwrap().contig_metadata[0].assignment =
+                    Assignment::Ambiguous;
+            },
+        );
+        states.push(members(&bins));
+
+        (history, bins, states)
+    }
+
+    #[test]
+    fn redo_after_partial_undo() {
+        let (mut history, mut bins, states) = three_events();
+
+        assert_eq!(history.undo(&mut bins).unwrap().id, 3);
+        assert_eq!(history.undo(&mut bins).unwrap().id, 2);
+        assert_eq!(members(&bins), states[1]);
+
+        assert_eq!(history.redo(&mut bins).unwrap().id, 2);
+        assert_eq!(members(&bins), states[2]);
+        assert_eq!(history.redo(&mut bins).unwrap().id, 3);
+        assert_eq!(members(&bins), states[3]);
+        assert!(history.redo(&mut bins).is_none());
+    }
+
+    #[test]
+    fn revert_to_undoes_later_events() {
+        let (history, mut bins, states) = three_events();
+        let events = history
+            .events()
+            .iter()
+            .cloned()
+            .map(|e| HistoryEvent {
+                timestamp: e.id * 1000,
+                ..e
+            })
+            .collect();
+        let mut history = History::from_events(events);
+
+        let reverted = history.revert_to(&mut bins, 1500);
+        assert_eq!(reverted.iter().map(|e| e.id).collect::<Vec<_>>(), [3, 2]);
+        assert_eq!(members(&bins), states[1]);
+
+        assert!(history.revert_to(&mut bins, 1500).is_empty());
+        let reverted = history.revert_to(&mut bins, 0);
+        assert_eq!(reverted.iter().map(|e| e.id).collect::<Vec<_>>(), [1]);
+        assert_eq!(members(&bins), states[0]);
+
+        for _ in 0..3 {
+            history.redo(&mut bins).unwrap();
+        }
+        assert_eq!(members(&bins), states[3]);
+    }
+
+    #[test]
+    fn recording_drops_undone_events() {
+        let (mut history, mut bins, states) = three_events();
+        history.undo(&mut bins).unwrap();
+        history.undo(&mut bins).unwrap();
+
+        let id = record(
+            &mut history,
+            &mut bins,
+            &["b"],
+            HistoryAction::CreateBin,
+            |bins| {
+                bins.remove(&bin_id("b"));
+            },
+        );
+        assert_eq!(id, 2);
+        assert_eq!(
+            history
+                .events()
+                .iter()
+                .map(|e| (e.id, e.action, e.undone))
+                .collect::<Vec<_>>(),
+            [
+                (1, HistoryAction::MoveContig, false),
+                (2, HistoryAction::CreateBin, false),
+            ]
+        );
+        assert!(history.redo(&mut bins).is_none());
+
+        history.undo(&mut bins).unwrap();
+        assert_eq!(members(&bins), states[1]);
+    }
+
+    #[test]
+    fn store_round_trip() {
+        let (mut history, mut bins, states) = three_events();
+        history.undo(&mut bins).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = ProjectStore::open(&dir.path().join("project.db")).unwrap();
+        store.save_curation(&bins, &history).unwrap();
+
+        let mut loaded_bins = store.load_bins().unwrap();
+        let mut loaded_history = store.load_history().unwrap();
+        assert_eq!(members(&loaded_bins), states[2]);
+        assert_eq!(loaded_history.events(), history.events());
+
+        loaded_history.redo(&mut loaded_bins).unwrap();
+        assert_eq!(members(&loaded_bins), states[3]);
+        loaded_history.revert_to(&mut loaded_bins, 0);
+        assert_eq!(members(&loaded_bins), states[0]);
+    }
+}
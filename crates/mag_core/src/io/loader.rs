@@ -0,0 +1,560 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{BufRead, Read},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use epimetheus_methylome::Motif;
+use rayon::iter::{ParallelBridge, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    contig::{Contig, ContigId},
+    errors::DataError,
+    io::reader::compression::open_input,
+    methylation::MotifSignature,
+};
+
+const DEFAULT_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+const LOG_EVERY_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Step of project creation or loading currently running.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, TS, PartialEq, Eq, Default)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum LoadPhase {
+    #[default]
+    Pending,
+    ContigBin,
+    Quality,
+    Methylation,
+    Assembly,
+    Coverage,
+    Taxonomy,
+    Saving,
+    Done,
+}
+
+impl LoadPhase {
+    const ALL: [LoadPhase; 9] = [
+        LoadPhase::Pending,
+        LoadPhase::ContigBin,
+        LoadPhase::Quality,
+        LoadPhase::Methylation,
+        LoadPhase::Assembly,
+        LoadPhase::Coverage,
+        LoadPhase::Taxonomy,
+        LoadPhase::Saving,
+        LoadPhase::Done,
+    ];
+}
+
+/// Progress of a project load, shared between the loading thread and whoever
+/// reports on it. Methylation bytes are counted after decompression.
+#[derive(Debug, Default)]
+pub struct LoadProgress {
+    phase: AtomicU8,
+    contig_bin_rows: AtomicU64,
+    quality_rows: AtomicU64,
+    rows: AtomicU64,
+    bytes: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct LoadProgressSnapshot {
+    pub phase: LoadPhase,
+    #[ts(type = "number")]
+    pub contig_bin_rows: u64,
+    #[ts(type = "number")]
+    pub quality_rows: u64,
+    #[ts(type = "number")]
+    pub methylation_rows: u64,
+    #[ts(type = "number")]
+    pub methylation_bytes: u64,
+}
+
+impl LoadProgress {
+    pub fn phase(&self) -> LoadPhase {
+        LoadPhase::ALL[self.phase.load(Ordering::Relaxed) as usize]
+    }
+
+    /// Move to `phase`. Fails if the load was cancelled, which is how the
+    /// loading code notices cancellation between phases.
+    pub fn enter(&self, phase: LoadPhase) -> Result<(), DataError> {
+        self.check_cancelled()?;
+        self.set_phase(phase);
+        Ok(())
+    }
+
+    /// Move to `phase` regardless of cancellation, for work that must not be
+    /// interrupted halfway.
+    pub fn set_phase(&self, phase: LoadPhase) {
+        tracing::debug!("Entering load phase {:?}", phase);
+        self.phase.store(phase as u8, Ordering::Relaxed);
+    }
+
+    pub fn set_contig_bin_rows(&self, rows: usize) {
+        self.contig_bin_rows.store(rows as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_quality_rows(&self, rows: usize) {
+        self.quality_rows.store(rows as u64, Ordering::Relaxed);
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn check_cancelled(&self) -> Result<(), DataError> {
+        if self.is_cancelled() {
+            Err(DataError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn snapshot(&self) -> LoadProgressSnapshot {
+        LoadProgressSnapshot {
+            phase: self.phase(),
+            contig_bin_rows: self.contig_bin_rows.load(Ordering::Relaxed),
+            quality_rows: self.quality_rows.load(Ordering::Relaxed),
+            methylation_rows: self.rows(),
+            methylation_bytes: self.bytes(),
+        }
+    }
+}
+
+/// Methylation row borrowing its strings from the csv record.
+#[derive(Deserialize)]
+pub(crate) struct BorrowedMethylationRecord<'a> {
+    pub contig: &'a str,
+    pub motif: &'a str,
+    pub mod_type: &'a str,
+    pub mod_position: u8,
+    pub methylation_value: f64,
+    pub mean_read_cov: f64,
+    pub n_motif_obs: u32,
+}
+
+/// Contigs parsed from one chunk, keyed by contig id.
+type PartialMethylation = HashMap<String, Contig>;
+
+/// Parses a nanomotif methylation table in chunks on the rayon thread pool.
+/// Each motif string is parsed once per chunk and shared by the rows of the
+/// chunk, and each contig id allocated once per chunk, instead of once per
+/// row.
+#[derive(Debug, Clone)]
+pub struct MethylationLoader {
+    chunk_size: usize,
+}
+
+impl Default for MethylationLoader {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl MethylationLoader {
+    /// Columns the loader reads. Other columns are ignored.
+    pub const COLUMNS: &[&str] = &[
+        "contig",
+        "motif",
+        "mod_type",
+        "mod_position",
+        "methylation_value",
+        "mean_read_cov",
+        "n_motif_obs",
+    ];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Approximate number of bytes handed to a worker at a time.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    pub fn load(
+        &self,
+        path: &Path,
+        progress: &LoadProgress,
+    ) -> Result<(HashMap<ContigId, Contig>, HashSet<Motif>), DataError> {
+        let start = Instant::now();
+        let result = self.load_from_reader(open_input(path)?, progress)?;
+
+        tracing::info!(
+            "Loaded {} methylation rows for {} contigs from {} in {:.1?}",
+            progress.rows(),
+            result.0.len(),
+            path.display(),
+            start.elapsed()
+        );
+        Ok(result)
+    }
+
+    pub fn load_from_reader<R>(
+        &self,
+        mut reader: R,
+        progress: &LoadProgress,
+    ) -> Result<(HashMap<ContigId, Contig>, HashSet<Motif>), DataError>
+    where
+        R: BufRead + Send,
+    {
+        let mut header = Vec::new();
+        reader.read_until(b'\n', &mut header)?;
+        if header.is_empty() {
+            return Err(DataError::DataAssertion(
+                "Methylation file is empty".to_string(),
+            ));
+        }
+        progress
+            .bytes
+            .fetch_add(header.len() as u64, Ordering::Relaxed);
+
+        let chunks = Chunks {
+            reader,
+            chunk_size: self.chunk_size,
+            progress,
+        };
+
+        let merged = chunks
+            .par_bridge()
+            .map(|chunk| parse_chunk(&header, &chunk?, progress))
+            .try_reduce(HashMap::new, merge)?;
+
+        let mut motifs = HashSet::new();
+        let contigs = merged
+            .into_values()
+            .map(|mut contig| {
+                for motif in contig.motifs.keys() {
+                    if !motifs.contains(motif.as_ref()) {
+                        motifs.insert(Motif::clone(motif));
+                    }
+                }
+                contig.mean_coverage = contig.derive_mean_coverage();
+                (contig.contig_id.clone(), contig)
+            })
+            .collect();
+
+        Ok((contigs, motifs))
+    }
+}
+
+/// Splits the input into blocks of whole lines.
+struct Chunks<'a, R> {
+    reader: R,
+    chunk_size: usize,
+    progress: &'a LoadProgress,
+}
+
+impl<R: BufRead> Iterator for Chunks<'_, R> {
+    type Item = Result<Vec<u8>, DataError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.progress.check_cancelled() {
+            return Some(Err(e));
+        }
+
+        let mut chunk = Vec::with_capacity(self.chunk_size + 1024);
+        let read = (&mut self.reader)
+            .take(self.chunk_size as u64)
+            .read_to_end(&mut chunk)
+            .and_then(|_| self.reader.read_until(b'\n', &mut chunk));
+        if let Err(e) = read {
+            return Some(Err(e.into()));
+        }
+        if chunk.is_empty() {
+            return None;
+        }
+
+        let before = self
+            .progress
+            .bytes
+            .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        let after = before + chunk.len() as u64;
+        if before / LOG_EVERY_BYTES != after / LOG_EVERY_BYTES {
+            tracing::info!(
+                "Read {} GiB of methylation data ({} rows parsed)",
+                after / LOG_EVERY_BYTES,
+                self.progress.rows()
+            );
+        }
+
+        Some(Ok(chunk))
+    }
+}
+
+fn parse_chunk(
+    header: &[u8],
+    chunk: &[u8],
+    progress: &LoadProgress,
+) -> Result<PartialMethylation, DataError> {
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(true)
+        .from_reader(header.chain(chunk));
+    let headers = rdr.byte_headers()?.clone();
+
+    // Keyed by motif sequence. The few mod type and position variants of a
+    // sequence are scanned linearly so lookups don't allocate.
+    let mut motif_cache: HashMap<String, Vec<(String, u8, Arc<Motif>)>> = HashMap::new();
+    let mut contigs: PartialMethylation = HashMap::new();
+    let mut record = csv::ByteRecord::new();
+    let mut n_rows = 0;
+
+    while rdr.read_byte_record(&mut record)? {
+        let row: BorrowedMethylationRecord = record.deserialize(Some(&headers))?;
+        n_rows += 1;
+
+        let cached = motif_cache.get(row.motif).and_then(|variants| {
+            variants
+                .iter()
+                .find(|(mod_type, pos, _)| mod_type == row.mod_type && *pos == row.mod_position)
+                .map(|(_, _, motif)| Arc::clone(motif))
+        });
+        let motif = match cached {
+            Some(motif) => motif,
+            None => {
+                let motif = Motif::new(row.motif, row.mod_type, row.mod_position)
+                    .map(Arc::new)
+                    .map_err(|e| {
+                        DataError::DataAssertion(format!(
+                            "Wrong motif mod: {}_{}_{}. Error: {}",
+                            row.motif, row.mod_type, row.mod_position, e
+                        ))
+                    })?;
+                motif_cache.entry(row.motif.to_string()).or_default().push((
+                    row.mod_type.to_string(),
+                    row.mod_position,
+                    Arc::clone(&motif),
+                ));
+                motif
+            }
+        };
+
+        let signature = MotifSignature {
+            motif: Arc::clone(&motif),
+            methylation_value: row.methylation_value,
+            n_motif_obs: row.n_motif_obs,
+            mean_coverage: row.mean_read_cov,
+        };
+
+        match contigs.get_mut(row.contig) {
+            Some(contig) => {
+                if contig.motifs.insert(motif, signature).is_some() {
+                    return Err(DataError::DataAssertion(format!(
+                        "Duplicate methylation row for contig '{}' and motif {}_{}_{}",
+                        row.contig, row.motif, row.mod_type, row.mod_position
+                    )));
+                }
+            }
+            None => {
+                contigs.insert(
+                    row.contig.to_string(),
+                    Contig {
+                        contig_id: ContigId(row.contig.to_string()),
+                        motifs: HashMap::from([(motif, signature)]),
+                        mean_coverage: 0.0,
+                        sequence: None,
+                        sample_depths: None,
+                        taxonomy: None,
+                    },
+                );
+            }
+        }
+    }
+    progress.rows.fetch_add(n_rows, Ordering::Relaxed);
+
+    Ok(contigs)
+}
+
+/// Chunks finish in no particular order, so a (contig, motif) pair seen in
+/// both halves is rejected rather than resolved by whichever chunk won.
+fn merge(a: PartialMethylation, b: PartialMethylation) -> Result<PartialMethylation, DataError> {
+    let (mut large, small) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+
+    for (contig_id, contig) in small {
+        match large.get_mut(&contig_id) {
+            Some(existing) => {
+                for (motif, signature) in contig.motifs {
+                    if existing.motifs.contains_key(&motif) {
+                        return Err(DataError::DataAssertion(format!(
+                            "Duplicate methylation row for contig '{}' and motif {}_{}_{}",
+                            contig_id,
+                            motif.sequence_to_string(),
+                            motif.mod_type.to_pileup_code(),
+                            motif.mod_position
+                        )));
+                    }
+                    existing.motifs.insert(motif, signature);
+                }
+            }
+            None => {
+                large.insert(contig_id, contig);
+            }
+        }
+    }
+    Ok(large)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::io::reader::methylation::MethReader;
+
+    const TABLE: &str = "\
+contig\tmotif\tmod_type\tmod_position\tmethylation_value\tmean_read_cov\tn_motif_obs\tmotif_occurences_total
+c1\tGATC\ta\t1\t0.86\t34.7\t9\t60
+c1\tCCWGG\tm\t1\t0.77\t22.3\t33\t60
+c2\tGATC\ta\t1\t0.12\t10.0\t4\t20
+c1\tGANTC\ta\t1\t0.5\t30.0\t12\t40
+c3\tCCWGG\tm\t1\t0.9\t5.5\t2\t8
+c2\tCCWGG\tm\t1\t0.33\t11.0\t7\t20
+c3\tGATC\ta\t1\t0.01\t6.5\t3\t8
+";
+
+    /// Methylation values keyed by contig and motif label.
+    type Flat = BTreeMap<(String, String), (f64, f64, u32)>;
+
+    fn label(motif: &Motif) -> String {
+        format!(
+            "{}_{}_{}",
+            motif.sequence_to_string(),
+            motif.mod_type.to_pileup_code(),
+            motif.mod_position
+        )
+    }
+
+    fn flatten(contigs: &HashMap<ContigId, Contig>) -> Flat {
+        contigs
+            .values()
+            .flat_map(|contig| {
+                contig.motifs.iter().map(|(motif, signature)| {
+                    assert_eq!(motif, &signature.motif);
+                    (
+                        (contig.contig_id.0.clone(), label(motif)),
+                        (
+                            signature.methylation_value,
+                            signature.mean_coverage,
+                            signature.n_motif_obs,
+                        ),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// What the sequential reader the loader replaced produced for `table`.
+    fn sequential(table: &str) -> Flat {
+        MethReader::from_reader(table.as_bytes())
+            .records()
+            .map(|record| {
+                let record = record.unwrap();
+                let contig = record.contig.clone();
+                let signature = MotifSignature::try_from(record).unwrap();
+                (
+                    (contig, label(&signature.motif)),
+                    (
+                        signature.methylation_value,
+                        signature.mean_coverage,
+                        signature.n_motif_obs,
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    fn load(table: &str, chunk_size: usize) -> Result<HashMap<ContigId, Contig>, DataError> {
+        let progress = LoadProgress::default();
+        let (contigs, motifs) = MethylationLoader::new()
+            .chunk_size(chunk_size)
+            .load_from_reader(table.as_bytes(), &progress)?;
+
+        let from_contigs: HashSet<&Motif> = contigs
+            .values()
+            .flat_map(|c| c.motifs.keys().map(|m| m.as_ref()))
+            .collect();
+        assert_eq!(from_contigs, motifs.iter().collect());
+        assert_eq!(progress.rows(), table.lines().count() as u64 - 1);
+        Ok(contigs)
+    }
+
+    fn assert_duplicate(result: Result<HashMap<ContigId, Contig>, DataError>) {
+        match result {
+            Err(DataError::DataAssertion(msg)) => {
+                assert!(msg.contains("Duplicate"), "{}", msg);
+                assert!(msg.contains("'c2'") && msg.contains("GATC_a_1"), "{}", msg);
+            }
+            other => panic!(
+                "expected a duplicate row error, got {:?}",
+                other.map(|c| c.len())
+            ),
+        }
+    }
+
+    #[test]
+    fn matches_sequential_reader_for_any_chunk_size() {
+        let expected = sequential(TABLE);
+        assert_eq!(expected.len(), 7);
+
+        for chunk_size in [1, 40, 100, DEFAULT_CHUNK_SIZE] {
+            let contigs = load(TABLE, chunk_size).unwrap();
+            assert_eq!(flatten(&contigs), expected, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    fn contig_split_across_chunks() {
+        // One line per chunk, so the rows of c1 end up in three chunks.
+        let contigs = load(TABLE, 1).unwrap();
+        let c1 = &contigs[&ContigId("c1".to_string())];
+
+        assert_eq!(c1.motifs.len(), 3);
+        assert!((c1.mean_coverage - (34.7 + 22.3 + 30.0) / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_duplicate_rows_in_one_chunk() {
+        let table = format!("{}c2\tGATC\ta\t1\t0.5\t10.0\t4\t20\n", TABLE);
+        assert_duplicate(load(&table, DEFAULT_CHUNK_SIZE));
+    }
+
+    #[test]
+    fn rejects_duplicate_rows_across_chunks() {
+        let table = format!("{}c2\tGATC\ta\t1\t0.5\t10.0\t4\t20\n", TABLE);
+        assert_duplicate(load(&table, 1));
+    }
+
+    #[test]
+    fn same_motif_with_other_mod_is_not_a_duplicate() {
+        let table = format!("{}c2\tGATC\tm\t3\t0.5\t10.0\t4\t20\n", TABLE);
+        let contigs = load(&table, DEFAULT_CHUNK_SIZE).unwrap();
+
+        assert_eq!(contigs[&ContigId("c2".to_string())].motifs.len(), 3);
+    }
+}
@@ -1 +1,3 @@
+pub mod loader;
 pub mod reader;
+pub mod writer;
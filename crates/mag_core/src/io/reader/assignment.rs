@@ -0,0 +1,57 @@
+use std::{io::BufRead, path::Path};
+
+use csv::ReaderBuilder;
+use serde::Deserialize;
+
+use crate::{
+    contig::Assignment,
+    errors::DataError,
+    io::reader::compression::{InputReader, open_input},
+};
+
+/// One curation decision: set `assignment` for `contig` in `bin`.
+#[derive(Debug, Deserialize)]
+pub struct AssignmentRecord {
+    pub bin: String,
+    pub contig: String,
+    pub assignment: Assignment,
+}
+
+pub struct AssignmentReader<R>
+where
+    R: std::io::Read,
+{
+    rdr: csv::Reader<R>,
+}
+
+impl<R> AssignmentReader<R>
+where
+    R: BufRead,
+{
+    pub fn from_reader(reader: R) -> Self {
+        let rdr = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .from_reader(reader);
+
+        Self { rdr }
+    }
+
+    pub fn records(&mut self) -> impl Iterator<Item = Result<AssignmentRecord, DataError>> + '_ {
+        self.rdr
+            .deserialize::<AssignmentRecord>()
+            .map(|r| r.map_err(DataError::from))
+    }
+
+    pub fn read_all(&mut self) -> Result<Vec<AssignmentRecord>, DataError> {
+        self.records().collect()
+    }
+}
+
+impl AssignmentReader<InputReader> {
+    pub fn new(path: &Path) -> Result<Self, DataError> {
+        let buf = open_input(path)?;
+
+        Ok(Self::from_reader(buf))
+    }
+}
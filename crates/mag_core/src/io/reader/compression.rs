@@ -0,0 +1,51 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use flate2::bufread::MultiGzDecoder;
+
+use crate::errors::DataError;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Buffered input returned by `open_input`.
+pub type InputReader = Box<dyn BufRead + Send>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression from the first bytes of a stream.
+    pub fn detect(magic: &[u8]) -> Self {
+        if magic.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if magic.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Open `path` for reading, decompressing gzip (including bgzip) and zstd
+/// files on the fly. The format is detected from the magic bytes, so the file
+/// extension does not matter.
+pub fn open_input(path: &Path) -> Result<InputReader, DataError> {
+    let mut buf = BufReader::new(File::open(path)?);
+    let compression = Compression::detect(buf.fill_buf()?);
+
+    let reader: InputReader = match compression {
+        Compression::None => Box::new(buf),
+        Compression::Gzip => Box::new(BufReader::new(MultiGzDecoder::new(buf))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::Decoder::with_buffer(buf)?)),
+    };
+
+    Ok(reader)
+}
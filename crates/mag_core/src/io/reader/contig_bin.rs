@@ -1,17 +1,21 @@
-use std::{fs::File, io::{BufRead, BufReader}, path::Path};
+use std::{io::BufRead, path::Path};
 
 use csv::{ReaderBuilder};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::{errors::DataError};
+use crate::{errors::DataError, io::reader::compression::{InputReader, open_input}};
 
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct ContigBinRecord {
     pub contig: String,
     pub bin: String,
 }
 
+impl ContigBinRecord {
+    pub const COLUMNS: &[&str] = &["contig", "bin"];
+}
+
 
 
 pub struct ContigBinReader<R>
@@ -44,10 +48,9 @@ where
 
 }
 
-impl ContigBinReader<BufReader<File>> {
+impl ContigBinReader<InputReader> {
     pub fn new(path: &Path) -> Result<Self, DataError> {
-        let file = File::open(path)?;
-        let buf = BufReader::new(file);
+        let buf = open_input(path)?;
 
         Ok(Self::from_reader(buf))
 
@@ -0,0 +1,145 @@
+use std::{collections::HashMap, io::BufRead, path::Path};
+
+use csv::ReaderBuilder;
+
+use crate::{
+    contig::ContigId,
+    errors::DataError,
+    io::reader::compression::{InputReader, open_input},
+};
+
+/// Layout of a depth table, detected from its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthFormat {
+    /// `jgi_summarize_bam_contig_depths`: `contigName contigLen totalAvgDepth`
+    /// followed by a depth and a `-var` column per sample.
+    MetaBat2,
+    /// `coverm contig`: the contig followed by one column per sample and
+    /// method, e.g. `sample.bam Mean`. Only `Mean` columns are used if there
+    /// are any.
+    CoverM,
+}
+
+impl DepthFormat {
+    pub fn detect(headers: &csv::StringRecord) -> Self {
+        if headers.get(0) == Some("contigName") && headers.get(2) == Some("totalAvgDepth") {
+            DepthFormat::MetaBat2
+        } else {
+            DepthFormat::CoverM
+        }
+    }
+
+    /// Index and name of every sample column.
+    fn sample_columns(&self, headers: &csv::StringRecord) -> Vec<(usize, String)> {
+        match self {
+            DepthFormat::MetaBat2 => headers
+                .iter()
+                .enumerate()
+                .skip(3)
+                .filter(|(_, h)| !h.ends_with("-var"))
+                .map(|(i, h)| (i, h.trim_end_matches(".bam").to_string()))
+                .collect(),
+            DepthFormat::CoverM => {
+                let columns = headers.iter().enumerate().skip(1);
+                if headers.iter().any(|h| h.ends_with(" Mean")) {
+                    columns
+                        .filter_map(|(i, h)| h.strip_suffix(" Mean").map(|h| (i, h)))
+                        .map(|(i, h)| (i, h.trim_end_matches(".bam").to_string()))
+                        .collect()
+                } else {
+                    columns
+                        .map(|(i, h)| (i, h.trim_end_matches(".bam").to_string()))
+                        .collect()
+                }
+            }
+        }
+    }
+}
+
+/// Mean depth of every contig in each sample. `depths` are in the order of
+/// `samples`.
+#[derive(Debug, Clone, Default)]
+pub struct DepthTable {
+    pub samples: Vec<String>,
+    pub depths: HashMap<ContigId, Vec<f64>>,
+}
+
+pub struct DepthReader<R>
+where
+    R: std::io::Read,
+{
+    rdr: csv::Reader<R>,
+}
+
+impl<R> DepthReader<R>
+where
+    R: BufRead,
+{
+    pub fn from_reader(reader: R) -> Self {
+        let rdr = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .from_reader(reader);
+
+        Self { rdr }
+    }
+
+    pub fn read_all(&mut self) -> Result<DepthTable, DataError> {
+        let headers = self.rdr.headers()?.clone();
+        let format = DepthFormat::detect(&headers);
+        let columns = format.sample_columns(&headers);
+        if columns.is_empty() {
+            return Err(DataError::DataAssertion(
+                "No sample columns found in depth table".to_string(),
+            ));
+        }
+
+        let mut depths = HashMap::new();
+        for record in self.rdr.records() {
+            let record = record?;
+            let contig = record.get(0).unwrap_or_default();
+            let values = columns
+                .iter()
+                .map(|(i, sample)| {
+                    let value = record.get(*i).unwrap_or_default();
+                    value.trim().parse::<f64>().map_err(|_| {
+                        DataError::DataAssertion(format!(
+                            "Depth '{}' of contig '{}' in sample '{}' is not a number",
+                            value, contig, sample
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<f64>, DataError>>()?;
+
+            if depths
+                .insert(ContigId(contig.to_string()), values)
+                .is_some()
+            {
+                return Err(DataError::DataAssertion(format!(
+                    "Contig '{}' appears more than once in the depth table",
+                    contig
+                )));
+            }
+        }
+
+        tracing::info!(
+            "Read {:?} depth table with {} contigs and {} samples",
+            format,
+            depths.len(),
+            columns.len()
+        );
+
+        Ok(DepthTable {
+            samples: columns.into_iter().map(|(_, sample)| sample).collect(),
+            depths,
+        })
+    }
+}
+
+impl DepthReader<InputReader> {
+    pub fn new(path: &Path) -> Result<Self, DataError> {
+        let buf = open_input(path)?;
+
+        Ok(Self::from_reader(buf))
+    }
+}
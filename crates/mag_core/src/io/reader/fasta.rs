@@ -0,0 +1,153 @@
+use std::{collections::HashMap, io::BufRead, path::Path};
+
+use crate::{
+    contig::{ContigId, SequenceStats},
+    errors::DataError,
+    io::{
+        loader::LoadProgress,
+        reader::compression::{InputReader, open_input},
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct FastaRecord {
+    pub id: String,
+    pub description: Option<String>,
+    pub sequence: Vec<u8>,
+}
+
+impl FastaRecord {
+    fn from_header(header: &str) -> Self {
+        let header = header.trim_end();
+        let (id, description) = match header.split_once(char::is_whitespace) {
+            Some((id, desc)) => (id.to_string(), Some(desc.trim().to_string())),
+            None => (header.to_string(), None),
+        };
+
+        Self {
+            id,
+            description,
+            sequence: Vec::new(),
+        }
+    }
+}
+
+pub struct FastaReader<R>
+where
+    R: std::io::Read,
+{
+    rdr: R,
+    line: Vec<u8>,
+    next_header: Option<String>,
+    line_number: usize,
+}
+
+impl<R> FastaReader<R>
+where
+    R: BufRead,
+{
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            rdr: reader,
+            line: Vec::new(),
+            next_header: None,
+            line_number: 0,
+        }
+    }
+
+    fn read_line(&mut self) -> Result<bool, DataError> {
+        self.line.clear();
+        let n = self.rdr.read_until(b'\n', &mut self.line)?;
+        self.line_number += 1;
+        while matches!(self.line.last(), Some(b'\n' | b'\r')) {
+            self.line.pop();
+        }
+        Ok(n > 0)
+    }
+
+    fn next_record(&mut self) -> Result<Option<FastaRecord>, DataError> {
+        let header = match self.next_header.take() {
+            Some(h) => h,
+            None => loop {
+                if !self.read_line()? {
+                    return Ok(None);
+                }
+                if self.line.is_empty() {
+                    continue;
+                }
+                if self.line[0] != b'>' {
+                    return Err(DataError::DataAssertion(format!(
+                        "Expected FASTA header on line {}",
+                        self.line_number
+                    )));
+                }
+                break String::from_utf8_lossy(&self.line[1..]).to_string();
+            },
+        };
+
+        let mut record = FastaRecord::from_header(&header);
+        while self.read_line()? {
+            if self.line.first() == Some(&b'>') {
+                self.next_header = Some(String::from_utf8_lossy(&self.line[1..]).to_string());
+                break;
+            }
+            record
+                .sequence
+                .extend(self.line.iter().filter(|b| !b.is_ascii_whitespace()));
+        }
+
+        Ok(Some(record))
+    }
+
+    pub fn records(&mut self) -> impl Iterator<Item = Result<FastaRecord, DataError>> + '_ {
+        std::iter::from_fn(move || self.next_record().transpose())
+    }
+}
+
+impl FastaReader<InputReader> {
+    pub fn new(path: &Path) -> Result<Self, DataError> {
+        let buf = open_input(path)?;
+
+        Ok(Self::from_reader(buf))
+    }
+}
+
+/// Length, GC content and N count of every sequence in `path`. Sequences are
+/// read one at a time, so only the stats are kept in memory.
+pub fn index_assembly(
+    path: &Path,
+    progress: &LoadProgress,
+) -> Result<HashMap<ContigId, SequenceStats>, DataError> {
+    let mut index = HashMap::new();
+    let mut reader = FastaReader::new(path)?;
+    for record in reader.records() {
+        let record = record?;
+        if index.len() % 10_000 == 0 {
+            progress.check_cancelled()?;
+        }
+        if index
+            .insert(
+                ContigId(record.id.clone()),
+                SequenceStats::from_sequence(&record.sequence),
+            )
+            .is_some()
+        {
+            return Err(DataError::DataAssertion(format!(
+                "Contig '{}' appears more than once in the assembly",
+                record.id
+            )));
+        }
+    }
+
+    Ok(index)
+}
+
+/// Bin name from the file name of a bin FASTA, as used by tools that name
+/// bins after their input files.
+pub fn strip_fasta_extension(name: &str) -> &str {
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    [".fasta", ".fna", ".fa"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+}
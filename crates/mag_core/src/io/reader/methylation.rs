@@ -1,9 +1,9 @@
-use std::{fs::File, io::{BufRead, BufReader}, path::Path};
+use std::{io::BufRead, path::Path};
 
 use csv::{ReaderBuilder};
 use serde::Deserialize;
 
-use crate::{errors::DataError};
+use crate::{errors::DataError, io::reader::compression::{InputReader, open_input}};
 
 
 #[derive(Debug, Deserialize)]
@@ -47,10 +47,9 @@ where
 
 }
 
-impl MethReader<BufReader<File>> {
+impl MethReader<InputReader> {
     pub fn new(path: &Path) -> Result<Self, DataError> {
-        let file = File::open(path)?;
-        let buf = BufReader::new(file);
+        let buf = open_input(path)?;
 
         Ok(Self::from_reader(buf))
 
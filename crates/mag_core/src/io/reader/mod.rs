@@ -1,5 +1,10 @@
 
 pub mod methylation;
 pub mod contig_bin;
-pub mod checkm2;
-
+pub mod fasta;
+pub mod assignment;
+pub mod compression;
+pub mod depth;
+pub mod taxonomy;
+pub mod quality;
+pub mod rna;
@@ -0,0 +1,488 @@
+use std::{io::BufRead, path::Path};
+
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    bin::BinStats,
+    errors::DataError,
+    io::reader::{compression::open_input, fasta::strip_fasta_extension},
+};
+
+/// Layout of a bin quality table. `Auto` detects the format from the header.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum QualityFormat {
+    #[default]
+    Auto,
+    /// CheckM2 `quality_report.tsv`.
+    Checkm2,
+    /// CheckM1 `qa` output, either the default aligned table or `--tab_table`.
+    Checkm1,
+    /// BUSCO `batch_summary.txt`. BUSCO has no contamination estimate, so
+    /// the percentage of duplicated BUSCOs is used in its place. It rises with
+    /// contamination from related genomes but barely shows contamination from
+    /// distant ones, so it underestimates contamination.
+    Busco,
+    /// Any tab separated table with the given columns.
+    Mapped(QualityColumns),
+}
+
+/// Column names of a user-mapped quality table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct QualityColumns {
+    pub bin: String,
+    pub completeness: String,
+    pub contamination: String,
+}
+
+impl QualityFormat {
+    /// Bin, completeness and contamination columns of the format.
+    pub fn columns(&self) -> Option<[&str; 3]> {
+        match self {
+            QualityFormat::Auto => None,
+            QualityFormat::Checkm2 => Some(["Name", "Completeness", "Contamination"]),
+            QualityFormat::Checkm1 => Some(["Bin Id", "Completeness", "Contamination"]),
+            QualityFormat::Busco => Some(["Input_file", "Complete", "Duplicated"]),
+            QualityFormat::Mapped(c) => Some([&c.bin, &c.completeness, &c.contamination]),
+        }
+    }
+
+    /// Optional columns holding the GC content, genome size, N50, contig
+    /// count, coding density and notes of a bin.
+    fn stats_columns(&self) -> [Option<&str>; 6] {
+        match self {
+            QualityFormat::Checkm2 => [
+                Some("GC_Content"),
+                Some("Genome_Size"),
+                Some("Contig_N50"),
+                Some("Total_Contigs"),
+                Some("Coding_Density"),
+                Some("Additional_Notes"),
+            ],
+            // Only present in the extended `qa -o 2` output.
+            QualityFormat::Checkm1 => [
+                Some("GC"),
+                Some("Genome size (bp)"),
+                Some("N50 (contigs)"),
+                Some("# contigs"),
+                Some("Coding density"),
+                None,
+            ],
+            _ => [None; 6],
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            QualityFormat::Auto => "auto",
+            QualityFormat::Checkm2 => "CheckM2",
+            QualityFormat::Checkm1 => "CheckM1",
+            QualityFormat::Busco => "BUSCO",
+            QualityFormat::Mapped(_) => "mapped",
+        }
+    }
+}
+
+/// Quality of one bin, whatever the table it came from.
+#[derive(Debug, Clone)]
+pub struct QualityRecord {
+    pub bin: String,
+    pub completeness: f64,
+    pub contamination: f64,
+    pub stats: BinStats,
+}
+
+/// A quality table read into memory. Quality tables have one row per bin, so
+/// they are small.
+#[derive(Debug, Clone)]
+pub struct QualityTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl QualityTable {
+    pub fn from_path(path: &Path) -> Result<Self, DataError> {
+        Self::from_reader(open_input(path)?)
+    }
+
+    /// Read a tab separated table, or the whitespace aligned table CheckM1
+    /// prints by default, where columns are separated by two or more spaces
+    /// and rows of dashes frame the header.
+    pub fn from_reader<R: BufRead>(mut reader: R) -> Result<Self, DataError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+
+        let is_frame = |line: &str| line.trim().is_empty() || line.trim_start().starts_with("---");
+        let aligned = text
+            .lines()
+            .find(|line| !is_frame(line))
+            .is_some_and(|header| !header.contains('\t'));
+        if aligned {
+            text = text
+                .lines()
+                .filter(|line| !is_frame(line))
+                .map(|line| {
+                    line.split("  ")
+                        .map(str::trim)
+                        .filter(|field| !field.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("\t")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+
+        let mut rdr = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .flexible(true)
+            .quoting(false)
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        let headers = rdr.headers()?.iter().map(str::to_string).collect();
+        let rows = rdr
+            .records()
+            .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+            .collect::<Result<_, DataError>>()?;
+
+        Ok(Self { headers, rows })
+    }
+
+    /// Guess the format from the header. A header with the columns of more
+    /// than one format is read as CheckM2, then CheckM1, then BUSCO.
+    pub fn detect(&self) -> Option<QualityFormat> {
+        [
+            QualityFormat::Checkm2,
+            QualityFormat::Checkm1,
+            QualityFormat::Busco,
+        ]
+        .into_iter()
+        .find(|format| self.missing_columns(format).is_empty())
+    }
+
+    /// Resolve `Auto` to the detected format.
+    pub fn resolve(&self, format: &QualityFormat) -> Result<QualityFormat, DataError> {
+        match format {
+            QualityFormat::Auto => self.detect().ok_or_else(|| {
+                DataError::DataAssertion(format!(
+                    "Unknown quality table with columns [{}]. Expected CheckM2, CheckM1 or BUSCO, or map the columns in the project",
+                    self.headers.join(", ")
+                ))
+            }),
+            format => Ok(format.clone()),
+        }
+    }
+
+    pub fn missing_columns(&self, format: &QualityFormat) -> Vec<String> {
+        format
+            .columns()
+            .into_iter()
+            .flatten()
+            .filter(|c| !self.headers.iter().any(|h| h == c))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Parse every row with `format`, which must not be `Auto`. Rows that
+    /// can't be parsed are returned as errors so callers can skip them.
+    pub fn records(&self, format: &QualityFormat) -> Vec<Result<QualityRecord, String>> {
+        let Some(columns) = format.columns() else {
+            return Vec::new();
+        };
+        let index: Vec<Option<usize>> = columns
+            .iter()
+            .map(|c| self.headers.iter().position(|h| h == c))
+            .collect();
+        let stats_index = format
+            .stats_columns()
+            .map(|c| c.and_then(|c| self.headers.iter().position(|h| h == c)));
+
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let field = |column: usize| {
+                    index[column]
+                        .and_then(|idx| row.get(idx))
+                        .map(|v| v.trim())
+                        .ok_or_else(|| format!("Row {} has no '{}' column", i + 1, columns[column]))
+                };
+                let number = |column: usize| {
+                    let value = field(column)?;
+                    value.parse::<f64>().map_err(|_| {
+                        format!(
+                            "Row {}: '{}' in column '{}' is not a number",
+                            i + 1,
+                            value,
+                            columns[column]
+                        )
+                    })
+                };
+
+                let bin = field(0)?;
+                let bin = match format {
+                    // BUSCO names bins after their input files.
+                    QualityFormat::Busco => strip_fasta_extension(bin),
+                    _ => bin,
+                };
+                // Statistics are informative only, so unparsable values are
+                // dropped rather than failing the row.
+                let stat = |column: usize| {
+                    stats_index[column]
+                        .and_then(|idx| row.get(idx))
+                        .map(|v| v.trim())
+                        .filter(|v| !v.is_empty() && *v != "None" && *v != "NA")
+                };
+                let real = |column: usize| stat(column).and_then(|v| v.parse::<f64>().ok());
+                // CheckM1 prints percentages where CheckM2 prints fractions.
+                let fraction = |column: usize| {
+                    real(column).map(|v| match format {
+                        QualityFormat::Checkm1 => v / 100.0,
+                        _ => v,
+                    })
+                };
+                let count = |column: usize| {
+                    stat(column).and_then(|v| {
+                        v.parse::<u64>()
+                            .ok()
+                            .or_else(|| v.parse::<f64>().ok().map(|v| v.round() as u64))
+                    })
+                };
+
+                Ok(QualityRecord {
+                    bin: bin.to_string(),
+                    completeness: number(1)?,
+                    contamination: number(2)?,
+                    stats: BinStats {
+                        gc_content: fraction(0),
+                        genome_size: count(1),
+                        n50: count(2),
+                        contig_count: count(3),
+                        coding_density: fraction(4),
+                        quality_notes: stat(5).map(str::to_string),
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+/// Read every bin quality in `path`. Fails on the first row that can't be
+/// parsed.
+pub fn read_quality(path: &Path, format: &QualityFormat) -> Result<Vec<QualityRecord>, DataError> {
+    let table = QualityTable::from_path(path)?;
+    let format = table.resolve(format)?;
+
+    let missing = table.missing_columns(&format);
+    if !missing.is_empty() {
+        return Err(DataError::DataAssertion(format!(
+            "Missing columns [{}] in {} quality table '{}'",
+            missing.join(", "),
+            format.name(),
+            path.display()
+        )));
+    }
+
+    tracing::info!(
+        "Reading {} quality table '{}'",
+        format.name(),
+        path.display()
+    );
+    table
+        .records(&format)
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(DataError::DataAssertion)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(text: &str) -> QualityTable {
+        QualityTable::from_reader(text.as_bytes()).unwrap()
+    }
+
+    fn parse(text: &str, format: &QualityFormat) -> (QualityFormat, Vec<QualityRecord>) {
+        let table = table(text);
+        let format = table.resolve(format).unwrap();
+        assert!(table.missing_columns(&format).is_empty());
+        let records = table
+            .records(&format)
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        (format, records)
+    }
+
+    fn summary(records: &[QualityRecord]) -> Vec<(&str, f64, f64)> {
+        records
+            .iter()
+            .map(|r| (r.bin.as_str(), r.completeness, r.contamination))
+            .collect()
+    }
+
+    #[test]
+    fn checkm2() {
+        let text = "\
+Name\tCompleteness\tContamination\tCompleteness_Model_Used\tCoding_Density\tContig_N50\tGenome_Size\tGC_Content\tTotal_Contigs\tAdditional_Notes
+bin1\t97.5\t1.2\tNeural Network (Specific Model)\t0.91\t25000\t3100000\t0.52\t140\tNone
+bin2\t55.0\t8.0\tGradient Boost (General Model)\t0.88\t4000\t1200000\t0.38\t600\tlow coverage
+";
+        let (format, records) = parse(text, &QualityFormat::Auto);
+
+        assert_eq!(format, QualityFormat::Checkm2);
+        assert_eq!(
+            summary(&records),
+            [("bin1", 97.5, 1.2), ("bin2", 55.0, 8.0)]
+        );
+        let stats = &records[0].stats;
+        assert_eq!(stats.gc_content, Some(0.52));
+        assert_eq!(stats.coding_density, Some(0.91));
+        assert_eq!(stats.genome_size, Some(3_100_000));
+        assert_eq!(stats.n50, Some(25_000));
+        assert_eq!(stats.contig_count, Some(140));
+        assert_eq!(stats.quality_notes, None);
+        assert_eq!(
+            records[1].stats.quality_notes.as_deref(),
+            Some("low coverage")
+        );
+    }
+
+    #[test]
+    fn checkm1_tab_table() {
+        let text = "\
+Bin Id\tMarker lineage\t# genomes\tCompleteness\tContamination\tStrain heterogeneity\tGC\tGenome size (bp)\tN50 (contigs)\t# contigs\tCoding density
+bin1\tk__Bacteria (UID203)\t5449\t97.41\t1.72\t0.00\t52.5\t3100000\t25000.0\t140\t91.0
+";
+        let (format, records) = parse(text, &QualityFormat::Auto);
+
+        assert_eq!(format, QualityFormat::Checkm1);
+        assert_eq!(summary(&records), [("bin1", 97.41, 1.72)]);
+        // Percentages are scaled to the fractions CheckM2 reports.
+        let stats = &records[0].stats;
+        assert!((stats.gc_content.unwrap() - 0.525).abs() < 1e-12);
+        assert!((stats.coding_density.unwrap() - 0.91).abs() < 1e-12);
+        assert_eq!(stats.n50, Some(25_000));
+        assert_eq!(stats.contig_count, Some(140));
+    }
+
+    #[test]
+    fn checkm1_aligned_table() {
+        let text = "\
+-------------------------------------------------------------------------------------------------------
+  Bin Id    Marker lineage          # genomes   # markers   Completeness   Contamination   Strain heterogeneity
+-------------------------------------------------------------------------------------------------------
+  bin1      k__Bacteria (UID203)      5449        104          97.41           1.72              0.00
+  bin.2     o__Bacillales (UID2)      4991        140          60.00           12.50             50.00
+-------------------------------------------------------------------------------------------------------
+";
+        let table = table(text);
+        assert_eq!(table.headers[0], "Bin Id");
+        assert_eq!(table.rows[0][1], "k__Bacteria (UID203)");
+
+        let (format, records) = parse(text, &QualityFormat::Auto);
+        assert_eq!(format, QualityFormat::Checkm1);
+        assert_eq!(
+            summary(&records),
+            [("bin1", 97.41, 1.72), ("bin.2", 60.0, 12.5)]
+        );
+        assert_eq!(records[0].stats.gc_content, None);
+    }
+
+    #[test]
+    fn busco_batch_summary() {
+        let text = "\
+Input_file\tDataset\tComplete\tSingle\tDuplicated\tFragmented\tMissing\tn_markers
+bin1.fa\tbacteria_odb10\t95.2\t94.0\t1.2\t2.4\t2.4\t124
+bin2.fasta.gz\tbacteria_odb10\t60.5\t58.1\t2.4\t8.1\t31.4\t124
+";
+        let (format, records) = parse(text, &QualityFormat::Auto);
+
+        assert_eq!(format, QualityFormat::Busco);
+        assert_eq!(
+            summary(&records),
+            [("bin1", 95.2, 1.2), ("bin2", 60.5, 2.4)]
+        );
+    }
+
+    #[test]
+    fn mapped_columns() {
+        let text = "\
+genome\tcomp\tcont\tother
+bin1.fa\t88\t3.5\tx
+";
+        let format = QualityFormat::Mapped(QualityColumns {
+            bin: "genome".to_string(),
+            completeness: "comp".to_string(),
+            contamination: "cont".to_string(),
+        });
+        let (resolved, records) = parse(text, &format);
+
+        assert_eq!(resolved, format);
+        // Only BUSCO names bins after their files.
+        assert_eq!(summary(&records), [("bin1.fa", 88.0, 3.5)]);
+        assert_eq!(records[0].stats, BinStats::default());
+    }
+
+    #[test]
+    fn bad_values_fail_the_row() {
+        let text = "Name\tCompleteness\tContamination\nbin1\tNA\t1.0\nbin2\t50\t1.0\n";
+        let table = table(text);
+        let records = table.records(&QualityFormat::Checkm2);
+
+        assert!(records[0].as_ref().unwrap_err().contains("'NA'"));
+        assert_eq!(records[1].as_ref().unwrap().bin, "bin2");
+    }
+
+    #[test]
+    fn detect_prefers_checkm2_over_checkm1() {
+        let table = table("Name\tBin Id\tCompleteness\tContamination\nbin1\tother\t90\t1\n");
+
+        assert_eq!(table.detect(), Some(QualityFormat::Checkm2));
+        let records = table.records(&QualityFormat::Checkm2);
+        assert_eq!(records[0].as_ref().unwrap().bin, "bin1");
+    }
+
+    #[test]
+    fn detect_prefers_checkm1_over_busco() {
+        let table = table(
+            "Bin Id\tInput_file\tComplete\tDuplicated\tCompleteness\tContamination\nbin1\tbin1.fa\t80\t2\t90\t1\n",
+        );
+
+        assert_eq!(table.detect(), Some(QualityFormat::Checkm1));
+    }
+
+    #[test]
+    fn explicit_format_overrides_detection() {
+        let table = table("Name\tBin Id\tCompleteness\tContamination\nbin1\tother\t90\t1\n");
+
+        let format = table.resolve(&QualityFormat::Checkm1).unwrap();
+        assert_eq!(format, QualityFormat::Checkm1);
+        assert_eq!(table.records(&format)[0].as_ref().unwrap().bin, "other");
+    }
+
+    #[test]
+    fn undetectable_table() {
+        for text in ["bin\tcompleteness\tcontamination\nbin1\t90\t1\n", ""] {
+            let table = table(text);
+
+            assert_eq!(table.detect(), None);
+            let err = table.resolve(&QualityFormat::Auto).unwrap_err().to_string();
+            assert!(err.contains("Unknown quality table"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn partial_checkm2_header_is_not_detected() {
+        // Completeness and Contamination alone don't say which column names
+        // the bin, so nothing is guessed.
+        let table = table("Genome\tCompleteness\tContamination\nbin1\t90\t1\n");
+
+        assert_eq!(table.detect(), None);
+        assert_eq!(table.missing_columns(&QualityFormat::Checkm2), ["Name"]);
+    }
+}
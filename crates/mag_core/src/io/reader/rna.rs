@@ -0,0 +1,73 @@
+use std::{io::BufRead, path::Path};
+
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bin::RnaCounts,
+    errors::DataError,
+    io::reader::compression::{InputReader, open_input},
+};
+
+/// rRNA and tRNA counts of one bin, e.g. summarised from barrnap and
+/// tRNAscan-SE. `trnas` is the number of distinct amino acids with a tRNA.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RnaRecord {
+    pub bin: String,
+    pub rrna_5s: u32,
+    pub rrna_16s: u32,
+    pub rrna_23s: u32,
+    pub trnas: u32,
+}
+
+impl RnaRecord {
+    pub const COLUMNS: &[&str] = &["bin", "rrna_5s", "rrna_16s", "rrna_23s", "trnas"];
+
+    pub fn counts(&self) -> RnaCounts {
+        RnaCounts {
+            rrna_5s: self.rrna_5s,
+            rrna_16s: self.rrna_16s,
+            rrna_23s: self.rrna_23s,
+            trnas: self.trnas,
+        }
+    }
+}
+
+pub struct RnaReader<R>
+where
+    R: std::io::Read,
+{
+    rdr: csv::Reader<R>,
+}
+
+impl<R> RnaReader<R>
+where
+    R: BufRead,
+{
+    pub fn from_reader(reader: R) -> Self {
+        let rdr = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(true)
+            .from_reader(reader);
+
+        Self { rdr }
+    }
+
+    pub fn records(&mut self) -> impl Iterator<Item = Result<RnaRecord, DataError>> + '_ {
+        self.rdr
+            .deserialize::<RnaRecord>()
+            .map(|r| r.map_err(DataError::from))
+    }
+
+    pub fn read_all(&mut self) -> Result<Vec<RnaRecord>, DataError> {
+        self.records().collect()
+    }
+}
+
+impl RnaReader<InputReader> {
+    pub fn new(path: &Path) -> Result<Self, DataError> {
+        let buf = open_input(path)?;
+
+        Ok(Self::from_reader(buf))
+    }
+}
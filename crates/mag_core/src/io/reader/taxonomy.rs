@@ -0,0 +1,220 @@
+use std::{collections::HashMap, io::BufRead, path::PathBuf};
+
+use csv::{ReaderBuilder, StringRecord};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    errors::DataError,
+    io::reader::{
+        compression::{InputReader, open_input},
+        fasta::strip_fasta_extension,
+    },
+};
+
+/// Taxonomy labels by bin or contig id.
+pub type Labels = HashMap<String, String>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum TaxonomyFormat {
+    /// `gtdbtk.*.summary.tsv`, one row per bin.
+    Gtdbtk,
+    /// BAT `bin2classification.txt`, one row per bin.
+    Bat,
+    /// CAT `contig2classification.txt`, optionally with `CAT add_names`.
+    Cat,
+    /// Kraken2 per-sequence output, with or without `--use-names`.
+    Kraken2,
+    /// MMseqs2 `easy-taxonomy` `_lca.tsv`.
+    Mmseqs2,
+}
+
+impl TaxonomyFormat {
+    /// Whether the labels are for bins rather than contigs.
+    pub fn is_bin_level(&self) -> bool {
+        matches!(self, TaxonomyFormat::Gtdbtk | TaxonomyFormat::Bat)
+    }
+}
+
+/// A classifier output to read labels from.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct TaxonomySource {
+    pub format: TaxonomyFormat,
+    pub path: PathBuf,
+}
+
+pub struct TaxonomyReader<R>
+where
+    R: std::io::Read,
+{
+    rdr: csv::Reader<R>,
+    format: TaxonomyFormat,
+}
+
+impl<R> TaxonomyReader<R>
+where
+    R: BufRead,
+{
+    pub fn from_reader(reader: R, format: TaxonomyFormat) -> Self {
+        let has_headers = matches!(
+            format,
+            TaxonomyFormat::Gtdbtk | TaxonomyFormat::Bat | TaxonomyFormat::Cat
+        );
+        let rdr = ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(has_headers)
+            .flexible(true)
+            .from_reader(reader);
+
+        Self { rdr, format }
+    }
+
+    /// Labels by bin or contig id. Unclassified entries are left out.
+    pub fn read_all(&mut self) -> Result<Labels, DataError> {
+        let headers = self.rdr.headers()?.clone();
+        let label_column = match self.format {
+            TaxonomyFormat::Gtdbtk => column(&headers, "classification")?,
+            TaxonomyFormat::Bat | TaxonomyFormat::Cat => column(&headers, "lineage")?,
+            TaxonomyFormat::Kraken2 | TaxonomyFormat::Mmseqs2 => 0,
+        };
+        let id_column = match self.format {
+            TaxonomyFormat::Kraken2 => 1,
+            _ => 0,
+        };
+
+        let mut labels = HashMap::new();
+        for record in self.rdr.records() {
+            let record = record?;
+            let Some(id) = record.get(id_column) else {
+                continue;
+            };
+            let label = match self.format {
+                TaxonomyFormat::Gtdbtk => record.get(label_column).and_then(gtdbtk_label),
+                // `CAT add_names` appends one column per rank after the scores.
+                TaxonomyFormat::Bat | TaxonomyFormat::Cat => {
+                    cat_label(&record, label_column, label_column + 2)
+                }
+                TaxonomyFormat::Kraken2 => kraken2_label(&record),
+                TaxonomyFormat::Mmseqs2 => mmseqs2_label(&record),
+            };
+            // BAT names bins after their FASTA files.
+            let id = match self.format {
+                TaxonomyFormat::Bat => strip_fasta_extension(id),
+                _ => id,
+            };
+            if let Some(label) = label {
+                labels.insert(id.to_string(), label);
+            }
+        }
+
+        Ok(labels)
+    }
+}
+
+impl TaxonomyReader<InputReader> {
+    pub fn new(source: &TaxonomySource) -> Result<Self, DataError> {
+        let buf = open_input(&source.path)?;
+
+        Ok(Self::from_reader(buf, source.format))
+    }
+}
+
+/// Read the labels of `source`.
+pub fn read_taxonomy(source: &TaxonomySource) -> Result<Labels, DataError> {
+    TaxonomyReader::new(source)?.read_all().map_err(|e| {
+        DataError::DataAssertion(format!(
+            "Could not read {:?} taxonomy '{}': {}",
+            source.format,
+            source.path.display(),
+            e
+        ))
+    })
+}
+
+fn column(headers: &StringRecord, name: &str) -> Result<usize, DataError> {
+    headers
+        .iter()
+        .position(|h| h == name)
+        .ok_or_else(|| DataError::DataAssertion(format!("Missing column '{}'", name)))
+}
+
+fn gtdbtk_label(classification: &str) -> Option<String> {
+    let classification = classification.trim();
+    (!classification.is_empty() && !classification.starts_with("Unclassified"))
+        .then(|| classification.to_string())
+}
+
+fn cat_label(row: &StringRecord, lineage: usize, names: usize) -> Option<String> {
+    let classification = row.get(1)?;
+    if classification.starts_with("no taxid") || classification == "unclassified" {
+        return None;
+    }
+
+    let named: Vec<&str> = row
+        .iter()
+        .skip(names)
+        .map(|name| name.split(':').next().unwrap_or_default().trim())
+        .filter(|name| !name.is_empty() && *name != "no support" && *name != "NA")
+        .collect();
+    if !named.is_empty() {
+        return Some(named.join(";"));
+    }
+
+    // Without names only the taxid of the deepest rank is useful.
+    let taxid = row
+        .get(lineage)?
+        .split(';')
+        .next_back()?
+        .trim_end_matches('*');
+    (!taxid.is_empty()).then(|| format!("taxid {}", taxid))
+}
+
+fn kraken2_label(row: &StringRecord) -> Option<String> {
+    if row.get(0)? != "C" {
+        return None;
+    }
+    let taxon = row.get(2)?.trim();
+    if taxon.parse::<u64>().is_ok() {
+        Some(format!("taxid {}", taxon))
+    } else {
+        Some(taxon.to_string())
+    }
+}
+
+fn mmseqs2_label(row: &StringRecord) -> Option<String> {
+    if row.get(1)? == "0" {
+        return None;
+    }
+
+    // With `--tax-lineage 1` the last column holds the full lineage.
+    let last = row.get(row.len() - 1)?;
+    if row.len() > 4 && last.contains(';') {
+        return Some(last.to_string());
+    }
+    row.get(3).map(|name| name.trim().to_string())
+}
+
+/// Read every source and split the labels into bin and contig labels.
+/// Later sources win for ids labelled more than once.
+pub fn read_sources(sources: &[TaxonomySource]) -> Result<(Labels, Labels), DataError> {
+    let mut bins = HashMap::new();
+    let mut contigs = HashMap::new();
+    for source in sources {
+        let labels = read_taxonomy(source)?;
+        tracing::info!(
+            "Read {} labels from {:?} taxonomy '{}'",
+            labels.len(),
+            source.format,
+            source.path.display()
+        );
+        if source.format.is_bin_level() {
+            bins.extend(labels);
+        } else {
+            contigs.extend(labels);
+        }
+    }
+
+    Ok((bins, contigs))
+}
@@ -0,0 +1,51 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::{errors::DataError, io::reader::fasta::FastaRecord};
+
+const LINE_WIDTH: usize = 80;
+
+pub struct FastaWriter<W>
+where
+    W: Write,
+{
+    wtr: W,
+}
+
+impl<W> FastaWriter<W>
+where
+    W: Write,
+{
+    pub fn from_writer(writer: W) -> Self {
+        Self { wtr: writer }
+    }
+
+    pub fn write_record(&mut self, record: &FastaRecord) -> Result<(), DataError> {
+        match &record.description {
+            Some(desc) => writeln!(self.wtr, ">{} {}", record.id, desc)?,
+            None => writeln!(self.wtr, ">{}", record.id)?,
+        }
+        for line in record.sequence.chunks(LINE_WIDTH) {
+            self.wtr.write_all(line)?;
+            self.wtr.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), DataError> {
+        self.wtr.flush()?;
+        Ok(())
+    }
+}
+
+impl FastaWriter<BufWriter<File>> {
+    pub fn new(path: &Path) -> Result<Self, DataError> {
+        let file = File::create(path)?;
+        let buf = BufWriter::new(file);
+
+        Ok(Self::from_writer(buf))
+    }
+}
@@ -1,5 +1,13 @@
+pub mod analysis;
 pub mod bin;
+pub mod clustering;
 pub mod contig;
+pub mod discovery;
 pub mod errors;
+pub mod export;
+pub mod history;
 pub mod io;
 pub mod methylation;
+pub mod project;
+pub mod store;
+pub mod validation;
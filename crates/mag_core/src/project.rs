@@ -0,0 +1,1051 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use epimetheus_methylome::Motif;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+    analysis::{
+        contamination::{ContigScore, ScoringOptions, score_bin},
+        reassignment::{BinCandidate, rank_candidate_bins},
+        recruitment::{Recruitment, UnbinnedContig, rank_recruits},
+    },
+    bin::{
+        Bin, BinId, BinMetadataRecord, ContigConflict, DuplicatePolicy, QualityProfile,
+        find_conflicts, move_contig,
+    },
+    contig::{Assignment, Contig, ContigAssignment, ContigId, SequenceStats},
+    errors::DataError,
+    export::{
+        AmbiguousPolicy,
+        contig_bin::{ContigBinExport, export_contig_bin},
+        fasta::{BinFastaExport, export_bins_fasta},
+    },
+    history::{BinChange, History, HistoryAction, HistoryEvent, PendingBinChange, now_millis},
+    io::{
+        loader::{LoadPhase, LoadProgress, MethylationLoader},
+        reader::{
+            contig_bin::ContigBinReader,
+            depth::{DepthReader, DepthTable},
+            fasta::index_assembly,
+            quality::{QualityFormat, read_quality},
+            rna::RnaReader,
+            taxonomy::{Labels, TaxonomySource, read_sources},
+        },
+    },
+    methylation::MotifSignature,
+    store::{ProjectStore, SourceFingerprint},
+};
+
+#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ProjectDetails {
+    pub project_id: String,
+    pub methylation_data_path: PathBuf,
+    pub contig_bin_path: PathBuf,
+    pub bin_quality_path: Option<PathBuf>,
+    #[serde(default)]
+    pub quality_format: QualityFormat,
+    /// Per-bin rRNA and tRNA counts for the MIMAG high quality criteria.
+    #[serde(default)]
+    #[ts(optional)]
+    pub rna_path: Option<PathBuf>,
+    #[serde(default)]
+    pub quality_profile: QualityProfile,
+    pub assembly_path: Option<PathBuf>,
+    /// Per-sample depth table from MetaBAT2 or CoverM.
+    #[serde(default)]
+    #[ts(optional)]
+    pub depth_path: Option<PathBuf>,
+    /// Bin and contig classifier outputs.
+    #[serde(default)]
+    pub taxonomy: Vec<TaxonomySource>,
+    pub output_path: PathBuf,
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+}
+
+impl ProjectDetails {
+    pub fn from_toml(path: &Path) -> Result<Self, DataError> {
+        let toml_str = std::fs::read_to_string(path).map_err(|e| {
+            DataError::NotFound(format!("Project file '{}': {}", path.display(), e))
+        })?;
+
+        toml::from_str(&toml_str).map_err(|e| DataError::ProjectFile(e.to_string()))
+    }
+
+    /// Input files the project was created from, by field name.
+    pub fn input_files(&self) -> Vec<(&'static str, &Path)> {
+        let mut files = vec![
+            (
+                "methylation_data_path",
+                self.methylation_data_path.as_path(),
+            ),
+            ("contig_bin_path", self.contig_bin_path.as_path()),
+        ];
+        if let Some(ref path) = self.bin_quality_path {
+            files.push(("bin_quality_path", path));
+        }
+        if let Some(ref path) = self.rna_path {
+            files.push(("rna_path", path));
+        }
+        if let Some(ref path) = self.assembly_path {
+            files.push(("assembly_path", path));
+        }
+        if let Some(ref path) = self.depth_path {
+            files.push(("depth_path", path));
+        }
+        for source in &self.taxonomy {
+            files.push(("taxonomy", &source.path));
+        }
+        files
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct MetadataUpdate {
+    pub bin: BinId,
+    pub contigs: Vec<ContigAssignment>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub user: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigMove {
+    pub from: BinId,
+    pub to: BinId,
+    #[serde(default)]
+    #[ts(optional)]
+    pub user: Option<String>,
+}
+
+/// Add an unbinned contig to `bin`.
+#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigRecruit {
+    pub bin: BinId,
+    #[serde(default)]
+    #[ts(optional)]
+    pub user: Option<String>,
+}
+
+/// Keep a contig only in `keep`, removing it from every other bin.
+#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ConflictResolution {
+    pub keep: BinId,
+    #[serde(default)]
+    #[ts(optional)]
+    pub user: Option<String>,
+}
+
+pub struct Project {
+    pub id: String,
+    pub outdir: PathBuf,
+    pub contig_metadata_path: PathBuf,
+    pub store_path: PathBuf,
+    pub assembly_path: Option<PathBuf>,
+    pub motifs: HashSet<Motif>,
+    pub bins: BTreeMap<BinId, Bin>,
+    pub contig_methylation: HashMap<ContigId, Contig>,
+    /// Samples of the depth table, empty without one.
+    pub depth_samples: Vec<String>,
+    pub history: History,
+    /// Milliseconds since the unix epoch of the last change, or of loading if
+    /// nothing changed since.
+    pub modified_at: u64,
+}
+
+/// Overview of a loaded project.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ProjectSummary {
+    pub project_id: String,
+    pub outdir: PathBuf,
+    pub bins: usize,
+    pub contigs: usize,
+    pub motifs: usize,
+    /// Contigs in more than one bin.
+    pub conflicts: usize,
+    /// Contigs with methylation data that are not in any bin.
+    pub unbinned: usize,
+    /// Rough estimate of the memory held by the project.
+    #[ts(type = "number")]
+    pub memory_bytes: u64,
+    #[ts(type = "number")]
+    pub modified_at: u64,
+}
+
+impl Project {
+    pub fn new(project_data: ProjectDetails) -> Result<Self, DataError> {
+        Self::new_with_progress(project_data, &LoadProgress::default())
+    }
+
+    /// Create a project, reporting each phase to `progress`. Cancelling
+    /// `progress` aborts the creation until saving starts. Cancelling later
+    /// is ignored so a project is never left half written.
+    pub fn new_with_progress(
+        project_data: ProjectDetails,
+        progress: &LoadProgress,
+    ) -> Result<Self, DataError> {
+        project_data
+            .quality_profile
+            .validate()
+            .map_err(DataError::ProjectFile)?;
+
+        progress.enter(LoadPhase::ContigBin)?;
+        let contig_bin = ContigBinReader::new(&project_data.contig_bin_path)?
+            .read_all()
+            .inspect_err(|e| {
+                tracing::error!("Error reading contig_bin file: {}", e.to_string());
+            })?;
+        progress.set_contig_bin_rows(contig_bin.len());
+
+        progress.enter(LoadPhase::Quality)?;
+        let quality = if let Some(ref p) = project_data.bin_quality_path {
+            read_quality(p, &project_data.quality_format).inspect_err(|e| {
+                tracing::error!("Error reading quality_file file: {}", e.to_string());
+            })?
+        } else {
+            Vec::new()
+        };
+        progress.set_quality_rows(quality.len());
+
+        let mut bins = Bin::from_records(contig_bin, quality, project_data.duplicate_policy)?;
+        if let Some(ref path) = project_data.rna_path {
+            Self::attach_rna(&mut bins, path).inspect_err(|e| {
+                tracing::error!("Error reading rRNA/tRNA file: {}", e.to_string());
+            })?;
+        }
+        Self::assess_quality(
+            &mut bins,
+            &project_data.quality_profile,
+            project_data.rna_path.is_some(),
+        );
+
+        if bins.is_empty() {
+            tracing::error!("No bins were collected from provided files");
+            return Err(DataError::DataAssertion(
+                "No bins were collected from provided files".to_string(),
+            ));
+        }
+
+        progress.enter(LoadPhase::Methylation)?;
+        let (mut contig_methylation, motifs) =
+            Self::load_methylation(&project_data.methylation_data_path, progress).inspect_err(
+                |e| {
+                    tracing::error!("Error reading methylation file: {}", e.to_string());
+                },
+            )?;
+
+        progress.enter(LoadPhase::Assembly)?;
+        let sequences = match project_data.assembly_path {
+            Some(ref path) => Some(index_assembly(path, progress).inspect_err(|e| {
+                tracing::error!("Error reading assembly: {}", e.to_string());
+            })?),
+            None => None,
+        };
+        if let Some(ref sequences) = sequences {
+            Self::attach_sequences(&mut contig_methylation, sequences);
+        }
+
+        progress.enter(LoadPhase::Coverage)?;
+        let depth_samples = match project_data.depth_path {
+            Some(ref path) => {
+                let table = DepthReader::new(path)?.read_all().inspect_err(|e| {
+                    tracing::error!("Error reading depth file: {}", e.to_string());
+                })?;
+                Self::attach_depths(&mut contig_methylation, table)
+            }
+            None => Vec::new(),
+        };
+
+        progress.enter(LoadPhase::Taxonomy)?;
+        let (bin_taxonomy, contig_taxonomy) = read_sources(&project_data.taxonomy)?;
+        Self::attach_taxonomy(
+            &mut bins,
+            &mut contig_methylation,
+            bin_taxonomy,
+            contig_taxonomy,
+        );
+
+        progress.enter(LoadPhase::Saving)?;
+
+        let metadata_path = project_data.output_path.join("contig_metadata.tsv");
+
+        // Create project toml
+        let toml = toml::to_string(&project_data.clone())
+            .map_err(|e| DataError::ProjectFile(e.to_string()))?;
+        let mut toml_path = project_data.output_path.clone();
+        toml_path.push("project.toml");
+
+        std::fs::create_dir_all(&project_data.output_path)?;
+
+        let mut file =
+            File::create(toml_path).map_err(|e| DataError::ProjectFile(e.to_string()))?;
+        write!(file, "{}", toml).map_err(|e| DataError::ProjectFile(e.to_string()))?;
+
+        let store_path = project_data.output_path.join("project.sqlite");
+        let mut store = ProjectStore::open(&store_path)?;
+        let source = SourceFingerprint::from_path(&project_data.methylation_data_path)?;
+        store.save_methylation(&source, &contig_methylation, &motifs)?;
+        if let (Some(path), Some(sequences)) = (&project_data.assembly_path, &sequences) {
+            store.save_sequences(&SourceFingerprint::from_path(path)?, sequences)?;
+        }
+
+        let project = Self {
+            id: project_data.project_id,
+            outdir: project_data.output_path,
+            contig_metadata_path: metadata_path,
+            store_path,
+            assembly_path: project_data.assembly_path,
+            motifs,
+            bins,
+            contig_methylation,
+            depth_samples,
+            history: History::default(),
+            modified_at: now_millis(),
+        };
+
+        project.save_metadata()?;
+        progress.set_phase(LoadPhase::Done);
+
+        Ok(project)
+    }
+
+    fn load_methylation(
+        path: &Path,
+        progress: &LoadProgress,
+    ) -> Result<(HashMap<ContigId, Contig>, HashSet<Motif>), DataError> {
+        MethylationLoader::new().load(path, progress)
+    }
+
+    pub fn load_from_path(path: PathBuf) -> Result<Self, DataError> {
+        Self::load_from_path_with_progress(path, &LoadProgress::default())
+    }
+
+    pub fn load_from_path_with_progress(
+        path: PathBuf,
+        progress: &LoadProgress,
+    ) -> Result<Self, DataError> {
+        let project_details = ProjectDetails::from_toml(&path)?;
+        project_details
+            .quality_profile
+            .validate()
+            .map_err(DataError::ProjectFile)?;
+
+        let store_path = project_details.output_path.join("project.sqlite");
+        let mut store = ProjectStore::open(&store_path)?;
+
+        progress.enter(LoadPhase::Methylation)?;
+        let (mut contig_methylation, motifs) = Self::load_cached_methylation(
+            &mut store,
+            &project_details.methylation_data_path,
+            progress,
+        )
+        .map_err(|e| match e {
+            DataError::Cancelled => e,
+            e => DataError::DataAssertion(format!("Error loading contig methylation data: {}", e)),
+        })?;
+
+        if let Some(ref path) = project_details.assembly_path {
+            progress.enter(LoadPhase::Assembly)?;
+            let sequences = Self::load_cached_sequences(&mut store, path, progress)?;
+            Self::attach_sequences(&mut contig_methylation, &sequences);
+        }
+
+        // The depth table is small enough to read on every load.
+        let depth_samples = match project_details.depth_path {
+            Some(ref path) => {
+                progress.enter(LoadPhase::Coverage)?;
+                let table = DepthReader::new(path).and_then(|mut r| r.read_all());
+                match table {
+                    Ok(table) => Self::attach_depths(&mut contig_methylation, table),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Could not read depth file '{}', loading without coverage: {}",
+                            path.display(),
+                            e
+                        );
+                        Vec::new()
+                    }
+                }
+            }
+            None => Vec::new(),
+        };
+
+        progress.enter(LoadPhase::ContigBin)?;
+
+        let metadata_path = project_details.output_path.join("contig_metadata.tsv");
+        let mut bins = if store.has_bins()? {
+            store.load_bins()?
+        } else {
+            // Projects created before the store existed only have the TSV.
+            tracing::info!(
+                "Importing bins from {} into project store",
+                metadata_path.display()
+            );
+            let bins = Self::read_metadata_tsv(&metadata_path)?;
+            store.save_bins(&bins)?;
+            bins
+        };
+        let history = store.load_history()?;
+        progress.set_contig_bin_rows(bins.values().map(|b| b.contig_metadata.len()).sum());
+
+        // The profile in project.toml may have been edited since the bins
+        // were saved, so quality is recomputed in memory. It is only written
+        // back with the next change to the project.
+        progress.enter(LoadPhase::Quality)?;
+        let rna_read = match project_details.rna_path {
+            Some(ref path) => match Self::attach_rna(&mut bins, path) {
+                Ok(()) => true,
+                Err(e) => {
+                    tracing::warn!(
+                        "Could not read rRNA/tRNA file '{}', keeping the saved bin quality: {}",
+                        path.display(),
+                        e
+                    );
+                    false
+                }
+            },
+            None => true,
+        };
+        if rna_read {
+            Self::assess_quality(
+                &mut bins,
+                &project_details.quality_profile,
+                project_details.rna_path.is_some(),
+            );
+        }
+
+        // Labels are not stored, so a missing classifier output only loses them.
+        progress.enter(LoadPhase::Taxonomy)?;
+        match read_sources(&project_details.taxonomy) {
+            Ok((bin_taxonomy, contig_taxonomy)) => Self::attach_taxonomy(
+                &mut bins,
+                &mut contig_methylation,
+                bin_taxonomy,
+                contig_taxonomy,
+            ),
+            Err(e) => tracing::warn!("Loading without taxonomy: {}", e),
+        }
+        progress.set_phase(LoadPhase::Done);
+
+        let project = Self {
+            id: project_details.project_id,
+            outdir: project_details.output_path,
+            contig_metadata_path: metadata_path,
+            store_path,
+            assembly_path: project_details.assembly_path,
+            motifs,
+            bins,
+            contig_methylation,
+            depth_samples,
+            history,
+            modified_at: now_millis(),
+        };
+
+        Ok(project)
+    }
+
+    /// Load methylation from the store if it was cached from the current
+    /// version of the methylation file. Otherwise parse the file and refresh
+    /// the cache.
+    fn load_cached_methylation(
+        store: &mut ProjectStore,
+        path: &Path,
+        progress: &LoadProgress,
+    ) -> Result<(HashMap<ContigId, Contig>, HashSet<Motif>), DataError> {
+        let source = SourceFingerprint::from_path(path)?;
+        if store.methylation_source()?.as_ref() == Some(&source) {
+            tracing::info!("Loading methylation data from project store");
+            return store.load_methylation();
+        }
+
+        let (contig_methylation, motifs) = Self::load_methylation(path, progress)?;
+        store.save_methylation(&source, &contig_methylation, &motifs)?;
+        Ok((contig_methylation, motifs))
+    }
+
+    /// Same as `load_cached_methylation` for the assembly index. If the
+    /// assembly can't be read anymore the cached index is used as is.
+    fn load_cached_sequences(
+        store: &mut ProjectStore,
+        path: &Path,
+        progress: &LoadProgress,
+    ) -> Result<HashMap<ContigId, SequenceStats>, DataError> {
+        let source = match SourceFingerprint::from_path(path) {
+            Ok(source) => source,
+            Err(e) => {
+                tracing::warn!(
+                    "Could not read assembly '{}', using the cached index: {}",
+                    path.display(),
+                    e
+                );
+                return store.load_sequences();
+            }
+        };
+        if store.assembly_source()?.as_ref() == Some(&source) {
+            tracing::info!("Loading assembly index from project store");
+            return store.load_sequences();
+        }
+
+        let sequences = index_assembly(path, progress)?;
+        store.save_sequences(&source, &sequences)?;
+        Ok(sequences)
+    }
+
+    fn attach_sequences(
+        contigs: &mut HashMap<ContigId, Contig>,
+        sequences: &HashMap<ContigId, SequenceStats>,
+    ) {
+        let mut missing = 0;
+        for contig in contigs.values_mut() {
+            contig.sequence = sequences.get(&contig.contig_id).copied();
+            if contig.sequence.is_none() {
+                missing += 1;
+            }
+        }
+        if missing > 0 {
+            tracing::warn!(
+                "{} contigs with methylation data are not in the assembly",
+                missing
+            );
+        }
+    }
+
+    /// Attach the depths of `table` to the contigs and return its samples.
+    fn attach_depths(contigs: &mut HashMap<ContigId, Contig>, table: DepthTable) -> Vec<String> {
+        let mut depths = table.depths;
+        let mut missing = 0;
+        for contig in contigs.values_mut() {
+            contig.sample_depths = depths.remove(&contig.contig_id);
+            if contig.sample_depths.is_none() {
+                missing += 1;
+            }
+        }
+        if missing > 0 {
+            tracing::warn!(
+                "{} contigs with methylation data are not in the depth table",
+                missing
+            );
+        }
+        table.samples
+    }
+
+    /// Attach rRNA and tRNA counts to the bins they are listed for.
+    fn attach_rna(bins: &mut BTreeMap<BinId, Bin>, path: &Path) -> Result<(), DataError> {
+        let records = RnaReader::new(path)?.read_all()?;
+        let mut counts: HashMap<String, _> = records
+            .into_iter()
+            .map(|r| (r.bin.clone(), r.counts()))
+            .collect();
+        let mut missing = 0;
+        for bin in bins.values_mut() {
+            bin.rna = counts.remove(&bin.id.0);
+            if bin.rna.is_none() {
+                missing += 1;
+            }
+        }
+        if missing > 0 {
+            tracing::warn!("{} bins are not in the rRNA/tRNA file", missing);
+        }
+        Ok(())
+    }
+
+    fn assess_quality(bins: &mut BTreeMap<BinId, Bin>, profile: &QualityProfile, has_rna: bool) {
+        for bin in bins.values_mut() {
+            bin.assess_quality(profile, has_rna);
+        }
+    }
+
+    fn attach_taxonomy(
+        bins: &mut BTreeMap<BinId, Bin>,
+        contigs: &mut HashMap<ContigId, Contig>,
+        mut bin_taxonomy: Labels,
+        mut contig_taxonomy: Labels,
+    ) {
+        for bin in bins.values_mut() {
+            bin.taxonomy = bin_taxonomy.remove(&bin.id.0);
+        }
+        for contig in contigs.values_mut() {
+            contig.taxonomy = contig_taxonomy.remove(&contig.contig_id.0);
+        }
+    }
+
+    fn read_metadata_tsv(metadata_path: &Path) -> Result<BTreeMap<BinId, Bin>, DataError> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .delimiter(b'\t')
+            .from_path(metadata_path)
+            .map_err(|e| {
+                DataError::ProjectFile(format!(
+                    "Could not create metadata reader from path {}. Error: {}",
+                    metadata_path.display(),
+                    e
+                ))
+            })?;
+
+        let mut bins: BTreeMap<BinId, Bin> = BTreeMap::new();
+        for rec in rdr.deserialize() {
+            let row: BinMetadataRecord =
+                rec.map_err(|e| DataError::ProjectFile(format!("Could not load bins: {}", e)))?;
+
+            let contig = row.contig_assignment();
+            bins.entry(row.id.clone())
+                .or_insert_with(|| Bin {
+                    stats: row.stats(),
+                    completeness: row.completeness,
+                    contamination: row.contamination,
+                    quality: row.quality,
+                    ..Bin::new(row.id)
+                })
+                .contig_metadata
+                .push(contig);
+        }
+
+        Ok(bins)
+    }
+
+    pub fn update_metadata(&mut self, metadata: MetadataUpdate) -> Result<(), DataError> {
+        if let Some((contig, tag)) = metadata
+            .contigs
+            .iter()
+            .find_map(|c| Some((&c.contig_id, c.invalid_tag()?)))
+        {
+            return Err(DataError::MetadataUpdate(format!(
+                "Invalid tag '{}' on contig '{}'. Tags can't be empty, contain ',' or start or end with whitespace.",
+                tag, contig.0
+            )));
+        }
+        // Assignments are keyed by bin and contig in the store.
+        let mut seen = HashSet::new();
+        if let Some(contig) = metadata
+            .contigs
+            .iter()
+            .map(|c| &c.contig_id)
+            .find(|id| !seen.insert(*id))
+        {
+            return Err(DataError::DataAssertion(format!(
+                "Contig '{}' is listed more than once for bin '{}'.",
+                contig.0, metadata.bin.0
+            )));
+        }
+        let change = BinChange::start(&self.bins, &metadata.bin);
+        let action = match self.bins.get_mut(&metadata.bin) {
+            Some(b) => {
+                if !b
+                    .contig_metadata
+                    .iter()
+                    .all(|c| metadata.contigs.contains(c))
+                    && b.contig_metadata.len() != metadata.contigs.len()
+                {
+                    return Err(DataError::MetadataUpdate(
+                        "Mismatch between contigs received and in bin. Change bin name."
+                            .to_string(),
+                    ));
+                }
+
+                b.contig_metadata = metadata.contigs;
+                HistoryAction::UpdateMetadata
+            }
+            None => {
+                let new_bin = Bin {
+                    contig_metadata: metadata.contigs,
+                    ..Bin::new(metadata.bin.clone())
+                };
+                self.bins.insert(metadata.bin.clone(), new_bin);
+                HistoryAction::CreateBin
+            }
+        };
+        self.history.record(
+            action,
+            metadata.user,
+            format!("Updated contigs of bin '{}'", metadata.bin.0),
+            vec![change.finish(&self.bins)],
+        );
+        self.modified_at = now_millis();
+        tracing::info!("Updated metadata");
+        Ok(())
+    }
+
+    /// Set the assignment of a single contig in a bin.
+    pub fn set_assignment(
+        &mut self,
+        bin: &BinId,
+        contig_id: &ContigId,
+        assignment: Assignment,
+        user: Option<&str>,
+    ) -> Result<(), DataError> {
+        let change = BinChange::start(&self.bins, bin);
+        let contig = self
+            .bins
+            .get_mut(bin)
+            .ok_or_else(|| DataError::NotFound(format!("Bin '{}'", bin.0)))?
+            .contig_metadata
+            .iter_mut()
+            .find(|c| &c.contig_id == contig_id)
+            .ok_or_else(|| {
+                DataError::NotFound(format!("Contig '{}' in bin '{}'", contig_id.0, bin.0))
+            })?;
+
+        contig.assignment = assignment;
+        self.history.record(
+            HistoryAction::SetAssignment,
+            user.map(str::to_string),
+            format!(
+                "Set contig '{}' in bin '{}' to {}",
+                contig_id.0, bin.0, assignment
+            ),
+            vec![change.finish(&self.bins)],
+        );
+        self.modified_at = now_millis();
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Option<HistoryEvent> {
+        let event = self.history.undo(&mut self.bins);
+        if let Some(ref e) = event {
+            tracing::info!("Undid: {}", e.description);
+            self.modified_at = now_millis();
+        }
+        event
+    }
+
+    pub fn redo(&mut self) -> Option<HistoryEvent> {
+        let event = self.history.redo(&mut self.bins);
+        if let Some(ref e) = event {
+            tracing::info!("Redid: {}", e.description);
+            self.modified_at = now_millis();
+        }
+        event
+    }
+
+    /// Undo every change made after `timestamp` (milliseconds since the unix
+    /// epoch).
+    pub fn revert_to(&mut self, timestamp: u64) -> Vec<HistoryEvent> {
+        let reverted = self.history.revert_to(&mut self.bins, timestamp);
+        tracing::info!("Reverted {} changes", reverted.len());
+        if !reverted.is_empty() {
+            self.modified_at = now_millis();
+        }
+        reverted
+    }
+
+    /// Commit the bins to the project store and refresh the
+    /// `contig_metadata.tsv` snapshot next to it.
+    pub fn save_metadata(&self) -> Result<(), DataError> {
+        tracing::info!("Saving metadata");
+        let mut store = ProjectStore::open(&self.store_path)?;
+        store.save_curation(&self.bins, &self.history)?;
+
+        // Write the snapshot to a temporary file first so a crash never leaves
+        // a truncated TSV behind.
+        let saved_path = self.contig_metadata_path.clone();
+        let tmp_path = saved_path.with_extension("tsv.tmp");
+
+        let mut wtr = csv::WriterBuilder::new()
+            .has_headers(true)
+            .delimiter(b'\t')
+            .from_path(&tmp_path)?;
+
+        for bin in self.bins.values() {
+            let records = bin.to_metadata_records();
+
+            for record in records {
+                wtr.serialize(record)?;
+            }
+        }
+        wtr.flush()?;
+        drop(wtr);
+        std::fs::rename(&tmp_path, &saved_path)?;
+
+        tracing::info!("metadata saved to: {}", saved_path.display());
+        Ok(())
+    }
+
+    pub fn score_contamination(
+        &self,
+        bin: &str,
+        options: &ScoringOptions,
+    ) -> Result<Vec<ContigScore>, DataError> {
+        let bin = self
+            .bins
+            .get(&BinId(bin.to_string()))
+            .ok_or_else(|| DataError::NotFound(format!("Bin '{}'", bin)))?;
+
+        Ok(score_bin(bin, &self.contig_methylation, options))
+    }
+
+    pub fn candidate_bins(
+        &self,
+        contig: &str,
+        options: &ScoringOptions,
+    ) -> Result<Vec<BinCandidate>, DataError> {
+        let contig = self
+            .contig_methylation
+            .get(&ContigId(contig.to_string()))
+            .ok_or_else(|| {
+                DataError::NotFound(format!("Methylation data for contig '{}'", contig))
+            })?;
+
+        Ok(rank_candidate_bins(
+            contig,
+            &self.bins,
+            &self.contig_methylation,
+            options,
+        ))
+    }
+
+    pub fn move_contig(&mut self, contig: &str, contig_move: ContigMove) -> Result<(), DataError> {
+        let changes = [
+            BinChange::start(&self.bins, &contig_move.from),
+            BinChange::start(&self.bins, &contig_move.to),
+        ];
+        move_contig(
+            &mut self.bins,
+            &ContigId(contig.to_string()),
+            &contig_move.from,
+            &contig_move.to,
+        )
+        .map_err(|e| DataError::MetadataUpdate(e.to_string()))?;
+
+        let description = format!(
+            "Moved contig '{}' from '{}' to '{}'",
+            contig, contig_move.from.0, contig_move.to.0
+        );
+        tracing::info!("{}", description);
+        self.history.record(
+            HistoryAction::MoveContig,
+            contig_move.user,
+            description,
+            changes.map(|c| c.finish(&self.bins)).to_vec(),
+        );
+        self.modified_at = now_millis();
+        Ok(())
+    }
+
+    /// Contigs with methylation data that are not in any bin, sorted by id.
+    pub fn unbinned_contigs(&self) -> Vec<&Contig> {
+        let binned: HashSet<&ContigId> = self
+            .bins
+            .values()
+            .flat_map(|b| b.contig_metadata.iter().map(|c| &c.contig_id))
+            .collect();
+
+        let mut unbinned: Vec<&Contig> = self
+            .contig_methylation
+            .values()
+            .filter(|c| !binned.contains(&c.contig_id))
+            .collect();
+        unbinned.sort_by(|a, b| a.contig_id.0.cmp(&b.contig_id.0));
+        unbinned
+    }
+
+    pub fn unbinned(&self) -> Vec<UnbinnedContig> {
+        self.unbinned_contigs()
+            .into_iter()
+            .map(UnbinnedContig::from_contig)
+            .collect()
+    }
+
+    /// Closest bin for every unbinned contig, closest first.
+    pub fn recruitment_candidates(&self, options: &ScoringOptions) -> Vec<Recruitment> {
+        rank_recruits(
+            &self.unbinned_contigs(),
+            &self.bins,
+            &self.contig_methylation,
+            options,
+        )
+    }
+
+    pub fn recruit_contig(
+        &mut self,
+        contig: &str,
+        recruit: ContigRecruit,
+    ) -> Result<(), DataError> {
+        let contig_id = ContigId(contig.to_string());
+        if !self.contig_methylation.contains_key(&contig_id) {
+            return Err(DataError::NotFound(format!(
+                "Methylation data for contig '{}'",
+                contig
+            )));
+        }
+        if let Some(bin) = self.bins.values().find(|b| b.contains(&contig_id)) {
+            return Err(DataError::DataAssertion(format!(
+                "Contig '{}' is already in bin '{}'. Move it instead.",
+                contig, bin.id.0
+            )));
+        }
+
+        let change = BinChange::start(&self.bins, &recruit.bin);
+        self.bins
+            .get_mut(&recruit.bin)
+            .ok_or_else(|| DataError::NotFound(format!("Bin '{}'", recruit.bin.0)))?
+            .contig_metadata
+            .push(ContigAssignment::new(contig_id, Assignment::None));
+
+        let description = format!("Recruited contig '{}' into '{}'", contig, recruit.bin.0);
+        tracing::info!("{}", description);
+        self.history.record(
+            HistoryAction::RecruitContig,
+            recruit.user,
+            description,
+            vec![change.finish(&self.bins)],
+        );
+        self.modified_at = now_millis();
+        Ok(())
+    }
+
+    /// Contigs that are currently in more than one bin.
+    pub fn conflicts(&self) -> Vec<ContigConflict> {
+        find_conflicts(&self.bins)
+    }
+
+    pub fn resolve_conflict(
+        &mut self,
+        contig: &str,
+        resolution: ConflictResolution,
+    ) -> Result<(), DataError> {
+        let contig_id = ContigId(contig.to_string());
+        if !self
+            .bins
+            .get(&resolution.keep)
+            .is_some_and(|b| b.contains(&contig_id))
+        {
+            return Err(DataError::NotFound(format!(
+                "Contig '{}' in bin '{}'",
+                contig, resolution.keep.0
+            )));
+        }
+
+        let others: Vec<BinId> = self
+            .bins
+            .values()
+            .filter(|b| b.id != resolution.keep && b.contains(&contig_id))
+            .map(|b| b.id.clone())
+            .collect();
+        if others.is_empty() {
+            return Err(DataError::DataAssertion(format!(
+                "Contig '{}' is only in bin '{}'.",
+                contig, resolution.keep.0
+            )));
+        }
+
+        let changes: Vec<PendingBinChange> = others
+            .iter()
+            .map(|bin| BinChange::start(&self.bins, bin))
+            .collect();
+        for bin in &others {
+            if let Some(b) = self.bins.get_mut(bin) {
+                b.remove_contig(&contig_id);
+            }
+        }
+
+        let description = format!(
+            "Kept contig '{}' in '{}', removed it from {}",
+            contig,
+            resolution.keep.0,
+            others
+                .iter()
+                .map(|b| format!("'{}'", b.0))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        tracing::info!("{}", description);
+        self.history.record(
+            HistoryAction::ResolveConflict,
+            resolution.user,
+            description,
+            changes.into_iter().map(|c| c.finish(&self.bins)).collect(),
+        );
+        self.modified_at = now_millis();
+        Ok(())
+    }
+
+    pub fn summary(&self) -> ProjectSummary {
+        ProjectSummary {
+            project_id: self.id.clone(),
+            outdir: self.outdir.clone(),
+            bins: self.bins.len(),
+            contigs: self.contig_methylation.len(),
+            motifs: self.motifs.len(),
+            conflicts: self.conflicts().len(),
+            unbinned: self.unbinned_contigs().len(),
+            memory_bytes: self.estimated_memory() as u64,
+            modified_at: self.modified_at,
+        }
+    }
+
+    /// Approximate heap usage. Counts the methylation table, which dominates,
+    /// and the bin assignments. Hash map overhead is not included.
+    fn estimated_memory(&self) -> usize {
+        let motif_size = |motif: &Motif| size_of::<Motif>() + motif.sequence.0.len();
+
+        let methylation: usize = self
+            .contig_methylation
+            .values()
+            .map(|contig| {
+                size_of::<(ContigId, Contig)>()
+                    + 2 * contig.contig_id.0.len()
+                    + contig
+                        .sample_depths
+                        .as_ref()
+                        .map_or(0, |d| d.len() * size_of::<f64>())
+                    + contig.motifs.len() * size_of::<(Arc<Motif>, MotifSignature)>()
+            })
+            .sum();
+
+        let assignments: usize = self
+            .bins
+            .values()
+            .flat_map(|bin| &bin.contig_metadata)
+            .map(|c| {
+                size_of::<ContigAssignment>()
+                    + c.contig_id.0.len()
+                    + c.note.as_ref().map_or(0, String::len)
+                    + c.tags.iter().map(String::len).sum::<usize>()
+            })
+            .sum();
+
+        methylation + assignments + self.motifs.iter().map(motif_size).sum::<usize>()
+    }
+
+    /// Write one FASTA per bin. Defaults to `<outdir>/bins`.
+    pub fn export_fasta(
+        &self,
+        output_dir: Option<PathBuf>,
+        policy: AmbiguousPolicy,
+    ) -> Result<Vec<BinFastaExport>, DataError> {
+        let assembly = self
+            .assembly_path
+            .as_ref()
+            .ok_or_else(|| DataError::NotFound(format!("Assembly for project '{}'", self.id)))?;
+        let output_dir = output_dir.unwrap_or_else(|| self.outdir.join("bins"));
+
+        tracing::info!("Exporting bin FASTA files to: {}", output_dir.display());
+        export_bins_fasta(assembly, &self.bins, &output_dir, policy)
+    }
+
+    /// Write the refined contig_bin table. Defaults to
+    /// `<outdir>/contig_bin_refined.tsv`.
+    pub fn export_contig_bin(
+        &self,
+        output_path: Option<PathBuf>,
+        policy: AmbiguousPolicy,
+    ) -> Result<ContigBinExport, DataError> {
+        let output_path = output_path.unwrap_or_else(|| self.outdir.join("contig_bin_refined.tsv"));
+
+        tracing::info!("Exporting refined contig_bin to: {}", output_path.display());
+        export_contig_bin(&self.bins, &output_path, policy)
+    }
+}
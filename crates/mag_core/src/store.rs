@@ -0,0 +1,569 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use epimetheus_methylome::Motif;
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+
+use crate::{
+    bin::{Bin, BinId, BinMetadataRecord, BinQuality, BinStats},
+    contig::{Assignment, Contig, ContigAssignment, ContigId, SequenceStats},
+    errors::DataError,
+    history::{History, HistoryEvent},
+    methylation::MotifSignature,
+};
+
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS bins (
+    bin_id TEXT PRIMARY KEY,
+    completeness REAL,
+    contamination REAL,
+    quality TEXT,
+    gc_content REAL,
+    genome_size INTEGER,
+    n50 INTEGER,
+    contig_count INTEGER,
+    coding_density REAL,
+    quality_notes TEXT
+);
+CREATE TABLE IF NOT EXISTS assignments (
+    bin_id TEXT NOT NULL,
+    contig_id TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    assignment TEXT NOT NULL,
+    note TEXT,
+    tags TEXT,
+    PRIMARY KEY (bin_id, contig_id)
+);
+CREATE TABLE IF NOT EXISTS motifs (
+    motif_id INTEGER PRIMARY KEY,
+    sequence TEXT NOT NULL,
+    mod_type TEXT NOT NULL,
+    mod_position INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS methylation (
+    contig_id TEXT NOT NULL,
+    motif_id INTEGER NOT NULL REFERENCES motifs(motif_id),
+    methylation_value REAL NOT NULL,
+    mean_coverage REAL NOT NULL,
+    n_motif_obs INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS sequences (
+    contig_id TEXT PRIMARY KEY,
+    length INTEGER NOT NULL,
+    gc_content REAL NOT NULL,
+    n_count INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS history (
+    event_id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    user TEXT,
+    action TEXT NOT NULL,
+    description TEXT NOT NULL,
+    changes TEXT NOT NULL,
+    undone INTEGER NOT NULL
+);
+";
+
+/// Identifies the version of an input file a cache was built from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFingerprint {
+    pub path: String,
+    pub size: u64,
+    pub modified: i64,
+}
+
+impl SourceFingerprint {
+    pub fn from_path(path: &Path) -> Result<Self, DataError> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as i64)
+            .unwrap_or_default();
+
+        Ok(Self {
+            path: path.display().to_string(),
+            size: metadata.len(),
+            modified,
+        })
+    }
+
+    fn to_meta_value(&self) -> String {
+        format!("{}\t{}\t{}", self.size, self.modified, self.path)
+    }
+
+    fn from_meta_value(value: &str) -> Option<Self> {
+        let mut fields = value.splitn(3, '\t');
+        Some(Self {
+            size: fields.next()?.parse().ok()?,
+            modified: fields.next()?.parse().ok()?,
+            path: fields.next()?.to_string(),
+        })
+    }
+}
+
+/// SQLite database holding the curation state of a project and parsed
+/// copies of its methylation table and assembly index. All writes happen in
+/// a transaction so an interrupted save leaves the previous state intact.
+pub struct ProjectStore {
+    conn: Connection,
+}
+
+impl ProjectStore {
+    pub fn open(path: &Path) -> Result<Self, DataError> {
+        let conn = Connection::open(path)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.execute_batch(SCHEMA)?;
+
+        let version: Option<i64> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?
+            .and_then(|v| v.parse().ok());
+
+        match version {
+            None => {
+                conn.execute(
+                    "INSERT OR REPLACE INTO meta (key, value) VALUES ('schema_version', ?1)",
+                    params![SCHEMA_VERSION.to_string()],
+                )?;
+            }
+            Some(v) if v > SCHEMA_VERSION => {
+                return Err(DataError::ProjectFile(format!(
+                    "Project store '{}' has schema version {} but only {} is supported",
+                    path.display(),
+                    v,
+                    SCHEMA_VERSION
+                )));
+            }
+            Some(_) => {}
+        }
+
+        Ok(Self { conn })
+    }
+
+    fn get_meta(&self, key: &str) -> Result<Option<String>, DataError> {
+        let value = self
+            .conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value)
+    }
+
+    pub fn has_bins(&self) -> Result<bool, DataError> {
+        let n: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM bins", [], |row| row.get(0))?;
+        Ok(n > 0)
+    }
+
+    pub fn save_bins(&mut self, bins: &BTreeMap<BinId, Bin>) -> Result<(), DataError> {
+        let tx = self.conn.transaction()?;
+        Self::write_bins(&tx, bins)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Save bins and their history in one transaction so the two never
+    /// disagree.
+    pub fn save_curation(
+        &mut self,
+        bins: &BTreeMap<BinId, Bin>,
+        history: &History,
+    ) -> Result<(), DataError> {
+        let tx = self.conn.transaction()?;
+        Self::write_bins(&tx, bins)?;
+        Self::write_history(&tx, history)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn write_bins(tx: &Transaction, bins: &BTreeMap<BinId, Bin>) -> Result<(), DataError> {
+        tx.execute("DELETE FROM assignments", [])?;
+        tx.execute("DELETE FROM bins", [])?;
+
+        let mut insert_bin = tx.prepare(
+            "INSERT INTO bins (bin_id, completeness, contamination, quality, gc_content,
+                               genome_size, n50, contig_count, coding_density, quality_notes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        let mut insert_assignment = tx.prepare(
+            "INSERT INTO assignments (bin_id, contig_id, position, assignment, note, tags)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+
+        for bin in bins.values() {
+            insert_bin.execute(params![
+                bin.id.0,
+                bin.completeness,
+                bin.contamination,
+                bin.quality.as_ref().map(|q| q.to_string()),
+                bin.stats.gc_content,
+                bin.stats.genome_size.map(|v| v as i64),
+                bin.stats.n50.map(|v| v as i64),
+                bin.stats.contig_count.map(|v| v as i64),
+                bin.stats.coding_density,
+                bin.stats.quality_notes,
+            ])?;
+            for (position, contig) in bin.contig_metadata.iter().enumerate() {
+                insert_assignment.execute(params![
+                    bin.id.0,
+                    contig.contig_id.0,
+                    position as i64,
+                    contig.assignment.to_string(),
+                    contig.note,
+                    BinMetadataRecord::join_tags(&contig.tags),
+                ])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_history(tx: &Transaction, history: &History) -> Result<(), DataError> {
+        tx.execute("DELETE FROM history", [])?;
+
+        let mut insert_event = tx.prepare(
+            "INSERT INTO history
+             (event_id, timestamp, user, action, description, changes, undone)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        for event in history.events() {
+            let changes = serde_json::to_string(&event.changes)
+                .map_err(|e| DataError::ProjectFile(e.to_string()))?;
+            insert_event.execute(params![
+                event.id as i64,
+                event.timestamp as i64,
+                event.user,
+                event.action.to_string(),
+                event.description,
+                changes,
+                event.undone,
+            ])?;
+        }
+
+        Ok(())
+    }
+
+    pub fn load_history(&self) -> Result<History, DataError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT event_id, timestamp, user, action, description, changes, undone
+             FROM history ORDER BY event_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, bool>(6)?,
+            ))
+        })?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let (id, timestamp, user, action, description, changes, undone) = row?;
+            events.push(HistoryEvent {
+                id: id as u64,
+                timestamp: timestamp as u64,
+                user,
+                action: action.parse().map_err(DataError::ProjectFile)?,
+                description,
+                changes: serde_json::from_str(&changes)
+                    .map_err(|e| DataError::ProjectFile(e.to_string()))?,
+                undone,
+            });
+        }
+
+        Ok(History::from_events(events))
+    }
+
+    pub fn load_bins(&self) -> Result<BTreeMap<BinId, Bin>, DataError> {
+        let mut bins: BTreeMap<BinId, Bin> = BTreeMap::new();
+
+        let mut stmt = self.conn.prepare(
+            "SELECT bin_id, completeness, contamination, quality, gc_content, genome_size,
+                        n50, contig_count, coding_density, quality_notes FROM bins",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let count = |idx: usize| -> rusqlite::Result<Option<u64>> {
+                Ok(row.get::<_, Option<i64>>(idx)?.map(|v| v as u64))
+            };
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<f64>>(1)?,
+                row.get::<_, Option<f64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                BinStats {
+                    gc_content: row.get(4)?,
+                    genome_size: count(5)?,
+                    n50: count(6)?,
+                    contig_count: count(7)?,
+                    coding_density: row.get(8)?,
+                    quality_notes: row.get(9)?,
+                },
+            ))
+        })?;
+        for row in rows {
+            let (bin_id, completeness, contamination, quality, stats) = row?;
+            let quality = quality
+                .map(|q| BinQuality::from_str(&q).map_err(DataError::BinQuality))
+                .transpose()?;
+
+            bins.insert(
+                BinId(bin_id.clone()),
+                Bin {
+                    completeness,
+                    contamination,
+                    quality,
+                    stats,
+                    ..Bin::new(BinId(bin_id))
+                },
+            );
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT bin_id, contig_id, assignment, note, tags FROM assignments
+             ORDER BY bin_id, position",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (bin_id, contig_id, assignment, note, tags) = row?;
+            let assignment = Assignment::from_str(&assignment).map_err(DataError::ProjectFile)?;
+
+            bins.get_mut(&BinId(bin_id.clone()))
+                .ok_or_else(|| DataError::ProjectFile(format!("Unknown bin '{}'", bin_id)))?
+                .contig_metadata
+                .push(ContigAssignment {
+                    contig_id: ContigId(contig_id),
+                    assignment,
+                    note,
+                    tags: BinMetadataRecord::split_tags(tags.as_deref()),
+                });
+        }
+
+        Ok(bins)
+    }
+
+    /// Fingerprint of the methylation file the cache was built from.
+    pub fn methylation_source(&self) -> Result<Option<SourceFingerprint>, DataError> {
+        Ok(self
+            .get_meta("methylation_source")?
+            .and_then(|v| SourceFingerprint::from_meta_value(&v)))
+    }
+
+    pub fn save_methylation(
+        &mut self,
+        source: &SourceFingerprint,
+        contigs: &HashMap<ContigId, Contig>,
+        motifs: &HashSet<Motif>,
+    ) -> Result<(), DataError> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM methylation", [])?;
+        tx.execute("DELETE FROM motifs", [])?;
+        {
+            let mut motif_ids: HashMap<&Motif, i64> = HashMap::new();
+            let mut insert_motif = tx.prepare(
+                "INSERT INTO motifs (motif_id, sequence, mod_type, mod_position)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut sorted_motifs = motifs.iter().collect::<Vec<_>>();
+            sorted_motifs.sort();
+            for (motif_id, motif) in sorted_motifs.into_iter().enumerate() {
+                insert_motif.execute(params![
+                    motif_id as i64,
+                    motif.sequence_to_string(),
+                    motif.mod_type.to_pileup_code(),
+                    motif.mod_position,
+                ])?;
+                motif_ids.insert(motif, motif_id as i64);
+            }
+
+            let mut insert_meth = tx.prepare(
+                "INSERT INTO methylation
+                 (contig_id, motif_id, methylation_value, mean_coverage, n_motif_obs)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for contig in contigs.values() {
+                for signature in contig.motifs.values() {
+                    insert_meth.execute(params![
+                        contig.contig_id.0,
+                        motif_ids[signature.motif.as_ref()],
+                        signature.methylation_value,
+                        signature.mean_coverage,
+                        signature.n_motif_obs,
+                    ])?;
+                }
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('methylation_source', ?1)",
+            params![source.to_meta_value()],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Fingerprint of the assembly the sequence index was built from.
+    pub fn assembly_source(&self) -> Result<Option<SourceFingerprint>, DataError> {
+        Ok(self
+            .get_meta("assembly_source")?
+            .and_then(|v| SourceFingerprint::from_meta_value(&v)))
+    }
+
+    pub fn save_sequences(
+        &mut self,
+        source: &SourceFingerprint,
+        sequences: &HashMap<ContigId, SequenceStats>,
+    ) -> Result<(), DataError> {
+        let tx = self.conn.transaction()?;
+        tx.execute("DELETE FROM sequences", [])?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO sequences (contig_id, length, gc_content, n_count)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for (contig_id, stats) in sequences {
+                insert.execute(params![
+                    contig_id.0,
+                    stats.length as i64,
+                    stats.gc_content,
+                    stats.n_count as i64,
+                ])?;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('assembly_source', ?1)",
+            params![source.to_meta_value()],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    pub fn load_sequences(&self) -> Result<HashMap<ContigId, SequenceStats>, DataError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT contig_id, length, gc_content, n_count FROM sequences")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                ContigId(row.get(0)?),
+                SequenceStats {
+                    length: row.get::<_, i64>(1)? as u64,
+                    gc_content: row.get(2)?,
+                    n_count: row.get::<_, i64>(3)? as u64,
+                },
+            ))
+        })?;
+
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn load_methylation(
+        &self,
+    ) -> Result<(HashMap<ContigId, Contig>, HashSet<Motif>), DataError> {
+        let mut motifs: HashMap<i64, Arc<Motif>> = HashMap::new();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT motif_id, sequence, mod_type, mod_position FROM motifs")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u8>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (motif_id, sequence, mod_type, mod_position) = row?;
+            let motif = Motif::new(&sequence, &mod_type, mod_position).map_err(|e| {
+                DataError::ProjectFile(format!(
+                    "Invalid cached motif {}_{}_{}: {}",
+                    sequence, mod_type, mod_position, e
+                ))
+            })?;
+            motifs.insert(motif_id, Arc::new(motif));
+        }
+
+        let mut contig_meth: HashMap<ContigId, Contig> = HashMap::new();
+        let mut stmt = self.conn.prepare(
+            "SELECT contig_id, motif_id, methylation_value, mean_coverage, n_motif_obs
+             FROM methylation",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, f64>(2)?,
+                row.get::<_, f64>(3)?,
+                row.get::<_, u32>(4)?,
+            ))
+        })?;
+        for row in rows {
+            let (contig_id, motif_id, methylation_value, mean_coverage, n_motif_obs) = row?;
+            let motif = motifs.get(&motif_id).ok_or_else(|| {
+                DataError::ProjectFile(format!("Unknown cached motif id {}", motif_id))
+            })?;
+
+            let signature = MotifSignature {
+                motif: Arc::clone(motif),
+                methylation_value,
+                n_motif_obs,
+                mean_coverage,
+            };
+            contig_meth
+                .entry(ContigId(contig_id.clone()))
+                .or_insert_with(|| Contig {
+                    contig_id: ContigId(contig_id),
+                    motifs: HashMap::new(),
+                    mean_coverage: 0.0,
+                    sequence: None,
+                    sample_depths: None,
+                    taxonomy: None,
+                })
+                .motifs
+                .insert(Arc::clone(motif), signature);
+        }
+
+        for contig in contig_meth.values_mut() {
+            contig.mean_coverage = contig.derive_mean_coverage();
+        }
+
+        Ok((
+            contig_meth,
+            motifs.into_values().map(Arc::unwrap_or_clone).collect(),
+        ))
+    }
+}
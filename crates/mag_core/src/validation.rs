@@ -0,0 +1,432 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use epimetheus_methylome::Motif;
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+    bin::DuplicatePolicy,
+    errors::DataError,
+    io::{
+        loader::{BorrowedMethylationRecord, MethylationLoader},
+        reader::{
+            compression::open_input,
+            contig_bin::ContigBinRecord,
+            quality::{QualityFormat, QualityTable},
+            rna::RnaRecord,
+        },
+    },
+    project::ProjectDetails,
+};
+
+/// Number of examples kept per finding. Totals are always exact.
+const MAX_EXAMPLES: usize = 20;
+
+/// Count of a finding with the first few occurrences.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct Sample<T> {
+    pub total: usize,
+    pub examples: Vec<T>,
+}
+
+impl<T> Default for Sample<T> {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            examples: Vec::new(),
+        }
+    }
+}
+
+impl<T> Sample<T> {
+    fn push(&mut self, item: T) {
+        self.total += 1;
+        if self.examples.len() < MAX_EXAMPLES {
+            self.examples.push(item);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+}
+
+impl<T> FromIterator<T> for Sample<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sample = Self::default();
+        for item in iter {
+            sample.push(item);
+        }
+        sample
+    }
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct FileReport {
+    /// `ProjectDetails` field the file came from.
+    pub name: String,
+    pub path: PathBuf,
+    pub exists: bool,
+    pub rows: usize,
+    pub missing_columns: Vec<String>,
+    pub extra_columns: Vec<String>,
+    /// Rows that could not be parsed.
+    pub errors: Sample<String>,
+}
+
+impl FileReport {
+    fn new(name: &str, path: &Path) -> Self {
+        Self {
+            name: name.to_string(),
+            path: path.to_path_buf(),
+            exists: path.is_file(),
+            rows: 0,
+            missing_columns: Vec::new(),
+            extra_columns: Vec::new(),
+            errors: Sample::default(),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.exists && self.missing_columns.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Motif that `Motif::new` rejects.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct InvalidMotif {
+    pub motif: String,
+    pub mod_type: String,
+    pub mod_position: u8,
+    pub error: String,
+    pub rows: usize,
+}
+
+/// Contig with more than one methylation row for the same motif. Creating
+/// the project fails on these.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct DuplicateMethylation {
+    pub contig: String,
+    pub motif: String,
+    pub mod_type: String,
+    pub mod_position: u8,
+}
+
+/// Contig listed more than once in the contig_bin file. Fails the preflight
+/// if it is in different bins and the project uses `DuplicatePolicy::Error`.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct DuplicateAssignment {
+    pub contig: String,
+    pub bins: Vec<String>,
+}
+
+/// Result of checking the inputs of a project before creating it. `ok` is
+/// false if an input is missing or `Project::new` would fail on it. The
+/// remaining findings are warnings.
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct PreflightReport {
+    pub ok: bool,
+    pub files: Vec<FileReport>,
+    pub invalid_motifs: Vec<InvalidMotif>,
+    pub duplicate_methylation: Sample<DuplicateMethylation>,
+    pub contigs_without_methylation: Sample<String>,
+    pub methylation_without_bin: Sample<String>,
+    pub bins_without_quality: Sample<String>,
+    pub duplicate_assignments: Sample<DuplicateAssignment>,
+}
+
+/// Check every input of `details` without building the project. Problems are
+/// collected in the report instead of stopping at the first one, and the
+/// methylation table is streamed so this works on large files.
+pub fn preflight(details: &ProjectDetails) -> PreflightReport {
+    let mut files = Vec::new();
+
+    // contig -> bins, in file order
+    let mut assignments: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    files.push(scan_table(
+        "contig_bin_path",
+        &details.contig_bin_path,
+        ContigBinRecord::COLUMNS,
+        |headers, record| {
+            let row: ContigBinRecord = record
+                .deserialize(Some(headers))
+                .map_err(|e| e.to_string())?;
+            assignments.entry(row.contig).or_default().push(row.bin);
+            Ok(())
+        },
+    ));
+    if files[0].is_ok() && assignments.is_empty() {
+        files[0]
+            .errors
+            .push("No bins were collected from the file".to_string());
+    }
+
+    // contig -> ids of the motifs it has a row for
+    let mut methylated: HashMap<String, HashSet<usize>> = HashMap::new();
+    // motif -> (id, rows)
+    let mut motifs: HashMap<(String, String, u8), (usize, usize)> = HashMap::new();
+    let mut duplicate_methylation = Sample::default();
+    files.push(scan_table(
+        "methylation_data_path",
+        &details.methylation_data_path,
+        MethylationLoader::COLUMNS,
+        |headers, record| {
+            let row: BorrowedMethylationRecord = record
+                .deserialize(Some(headers))
+                .map_err(|e| e.to_string())?;
+            let n_motifs = motifs.len();
+            let (motif_id, rows) = motifs
+                .entry((
+                    row.motif.to_string(),
+                    row.mod_type.to_string(),
+                    row.mod_position,
+                ))
+                .or_insert((n_motifs, 0));
+            *rows += 1;
+
+            let seen = match methylated.get_mut(row.contig) {
+                Some(seen) => seen,
+                None => methylated.entry(row.contig.to_string()).or_default(),
+            };
+            if !seen.insert(*motif_id) {
+                duplicate_methylation.push(DuplicateMethylation {
+                    contig: row.contig.to_string(),
+                    motif: row.motif.to_string(),
+                    mod_type: row.mod_type.to_string(),
+                    mod_position: row.mod_position,
+                });
+            }
+            Ok(())
+        },
+    ));
+
+    let mut quality_bins: HashSet<String> = HashSet::new();
+    let has_quality = match details.bin_quality_path {
+        Some(ref path) => {
+            files.push(scan_quality(
+                path,
+                &details.quality_format,
+                &mut quality_bins,
+            ));
+            true
+        }
+        None => false,
+    };
+
+    if let Some(ref path) = details.rna_path {
+        files.push(scan_table(
+            "rna_path",
+            path,
+            RnaRecord::COLUMNS,
+            |headers, record| {
+                record
+                    .deserialize::<RnaRecord>(Some(headers))
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            },
+        ));
+    }
+
+    // Files that are not tables only need to exist.
+    let checked: HashSet<String> = files.iter().map(|f| f.name.clone()).collect();
+    for (name, path) in details.input_files() {
+        if !checked.contains(name) {
+            files.push(FileReport::new(name, path));
+        }
+    }
+
+    let mut invalid_motifs: Vec<InvalidMotif> = motifs
+        .into_iter()
+        .filter_map(|((motif, mod_type, mod_position), (_, rows))| {
+            Motif::new(&motif, &mod_type, mod_position)
+                .err()
+                .map(|e| InvalidMotif {
+                    motif,
+                    mod_type,
+                    mod_position,
+                    error: e.to_string(),
+                    rows,
+                })
+        })
+        .collect();
+    invalid_motifs.sort_by(|a, b| (&a.motif, &a.mod_type).cmp(&(&b.motif, &b.mod_type)));
+
+    let mut bins: Vec<&String> = assignments.values().flatten().collect();
+    bins.sort();
+    bins.dedup();
+    let bins_without_quality = if has_quality {
+        bins.into_iter()
+            .filter(|b| !quality_bins.contains(*b))
+            .cloned()
+            .collect()
+    } else {
+        Sample::default()
+    };
+
+    let contigs_without_methylation = assignments
+        .keys()
+        .filter(|c| !methylated.contains_key(*c))
+        .cloned()
+        .collect();
+
+    let mut methylation_without_bin: Vec<String> = methylated
+        .into_keys()
+        .filter(|c| !assignments.contains_key(c))
+        .collect();
+    methylation_without_bin.sort();
+
+    let mut multi_bin = false;
+    let duplicate_assignments = assignments
+        .into_iter()
+        .filter(|(_, bins)| bins.len() > 1)
+        .map(|(contig, bins)| {
+            multi_bin |= bins.iter().any(|b| b != &bins[0]);
+            DuplicateAssignment { contig, bins }
+        })
+        .collect();
+    let refused = multi_bin && details.duplicate_policy == DuplicatePolicy::Error;
+
+    let ok = files.iter().all(FileReport::is_ok)
+        && invalid_motifs.is_empty()
+        && duplicate_methylation.is_empty()
+        && !refused;
+
+    PreflightReport {
+        ok,
+        files,
+        invalid_motifs,
+        duplicate_methylation,
+        contigs_without_methylation,
+        methylation_without_bin: methylation_without_bin.into_iter().collect(),
+        bins_without_quality,
+        duplicate_assignments,
+    }
+}
+
+/// Check a quality table in any of the supported formats, collecting the
+/// bins it has quality for.
+fn scan_quality(path: &Path, format: &QualityFormat, bins: &mut HashSet<String>) -> FileReport {
+    let mut report = FileReport::new("bin_quality_path", path);
+    if !report.exists {
+        return report;
+    }
+
+    let table = match QualityTable::from_path(path) {
+        Ok(table) => table,
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+    let format = match table.resolve(format) {
+        Ok(format) => format,
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+
+    report.missing_columns = table.missing_columns(&format);
+    let expected = format.columns().unwrap_or_default();
+    report.extra_columns = table
+        .headers
+        .iter()
+        .filter(|h| !expected.contains(&h.as_str()))
+        .cloned()
+        .collect();
+    if !report.missing_columns.is_empty() {
+        return report;
+    }
+
+    report.rows = table.rows.len();
+    for record in table.records(&format) {
+        match record {
+            Ok(record) => {
+                bins.insert(record.bin);
+            }
+            Err(e) => report.errors.push(e),
+        }
+    }
+    report
+}
+
+/// Read a tab separated table, comparing its header with `expected` and
+/// handing each row to `visit`. Rows are only read if no column is missing.
+fn scan_table<F>(name: &str, path: &Path, expected: &[&str], mut visit: F) -> FileReport
+where
+    F: FnMut(&csv::ByteRecord, &csv::ByteRecord) -> Result<(), String>,
+{
+    let mut report = FileReport::new(name, path);
+    if !report.exists {
+        return report;
+    }
+
+    if let Err(e) = scan_rows(&mut report, expected, &mut visit) {
+        report.errors.push(e.to_string());
+    }
+    report
+}
+
+fn scan_rows<F>(report: &mut FileReport, expected: &[&str], visit: &mut F) -> Result<(), DataError>
+where
+    F: FnMut(&csv::ByteRecord, &csv::ByteRecord) -> Result<(), String>,
+{
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(true)
+        .from_reader(open_input(&report.path)?);
+
+    let headers = rdr.byte_headers()?.clone();
+    let found: Vec<String> = headers
+        .iter()
+        .map(|h| String::from_utf8_lossy(h).into_owned())
+        .collect();
+    report.missing_columns = expected
+        .iter()
+        .filter(|c| !found.iter().any(|f| f == *c))
+        .map(|c| c.to_string())
+        .collect();
+    report.extra_columns = found
+        .iter()
+        .filter(|f| !expected.contains(&f.as_str()))
+        .cloned()
+        .collect();
+    if !report.missing_columns.is_empty() {
+        return Ok(());
+    }
+
+    let mut record = csv::ByteRecord::new();
+    loop {
+        match rdr.read_byte_record(&mut record) {
+            Ok(false) => break,
+            Ok(true) => {
+                report.rows += 1;
+                if let Err(e) = visit(&headers, &record) {
+                    report.errors.push(e);
+                }
+            }
+            Err(e) => {
+                report.rows += 1;
+                report.errors.push(e.to_string());
+                // Only per-record errors can be skipped. Anything else means
+                // the rest of the file can't be read.
+                if !matches!(
+                    e.kind(),
+                    csv::ErrorKind::UnequalLengths { .. } | csv::ErrorKind::Utf8 { .. }
+                ) {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(())
+}
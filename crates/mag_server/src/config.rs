@@ -0,0 +1,34 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+/// contam-map API server.
+#[derive(Parser, Debug, Clone)]
+#[command(name = "mag_server", version)]
+pub struct ServerConfig {
+    /// Address to bind to
+    #[arg(long, env = "CONTAM_MAP_HOST", default_value = "0.0.0.0")]
+    pub host: String,
+
+    /// Port to listen on
+    #[arg(short, long, env = "CONTAM_MAP_PORT", default_value_t = 3000)]
+    pub port: u16,
+
+    /// Directory that relative project output and project.toml paths are resolved against
+    #[arg(long, env = "CONTAM_MAP_PROJECTS_DIR")]
+    pub projects_dir: Option<PathBuf>,
+
+    /// Log filter, e.g. `info` or `mag_server=debug`. Falls back to RUST_LOG
+    #[arg(long, env = "CONTAM_MAP_LOG")]
+    pub log_level: Option<String>,
+
+    /// project.toml files to load on startup
+    #[arg(long = "load", env = "CONTAM_MAP_LOAD", value_delimiter = ',')]
+    pub load: Vec<PathBuf>,
+}
+
+impl ServerConfig {
+    pub fn bind_address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
@@ -1,4 +1,5 @@
 use axum::{Json, http::StatusCode, response::IntoResponse};
+use mag_core::errors::DataError;
 use serde::Serialize;
 use thiserror::Error;
 use ts_rs::TS;
@@ -9,7 +10,7 @@ pub enum ApiError {
     DirectoryCreation(#[from] std::io::Error),
 
     #[error("Failed to load data: {0}")]
-    DataLoad(#[from] mag_core::errors::DataError),
+    DataLoad(mag_core::errors::DataError),
 
     #[error("Project id '{0}' Already Exists. Close existing.")]
     ProjectExists(String),
@@ -28,6 +29,23 @@ pub enum ApiError {
 
     #[error("Query error: {0}")]
     Query(String),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("A request failed while holding the lock. Reload the project.")]
+    LockPoisoned,
+}
+
+impl From<DataError> for ApiError {
+    fn from(err: DataError) -> Self {
+        match err {
+            DataError::ProjectFile(msg) => ApiError::ProjectFileCreation(msg),
+            DataError::MetadataUpdate(msg) => ApiError::MetadataUpdate(msg),
+            DataError::NotFound(msg) => ApiError::NotFound(msg),
+            err => ApiError::DataLoad(err),
+        }
+    }
 }
 
 #[derive(Serialize, TS)]
@@ -50,6 +68,8 @@ impl IntoResponse for ApiError {
             ApiError::MetadataUpdate(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             ApiError::Io(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             ApiError::Query(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, format!("Not found: {}", msg)),
+            ApiError::LockPoisoned => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
         let body = Json(ErrorResponse {
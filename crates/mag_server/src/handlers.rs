@@ -1,31 +1,63 @@
-use std::path::PathBuf;
+use std::{collections::HashSet, path::PathBuf};
 
 use axum::{
     Json, debug_handler,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+};
+use mag_core::{
+    analysis::{
+        contamination::{ContigScore, ScoringOptions},
+        reassignment::BinCandidate,
+        recruitment::{Recruitment, UnbinnedContig},
+    },
+    bin::{Bin, ContigConflict},
+    discovery,
+    export::{contig_bin::ContigBinExport, fasta::BinFastaExport},
+    history::HistoryEvent,
+    project::ProjectSummary,
+    validation::{self, PreflightReport},
 };
-use mag_core::bin::Bin;
 
 use crate::{
     SharedState,
     errors::ApiError,
-    models::{HeatmapData, MetadataUpdate, MethDataFilters, ProjectDetails},
+    heatmap::get_heatmap_data,
+    jobs::{self, JobState, JobStatus},
+    models::{
+        BinQueryParams, CloseOptions, ConflictResolution, ContigBinExportRequest, ContigMove,
+        ContigRecruit, FastaExportRequest, HeatmapData, MetadataUpdate, MethDataFilters,
+        ProjectDetails, ProjectListing, RevertRequest, export_path, read_lock, with_project,
+        with_project_mut, write_lock,
+    },
 };
 
 #[debug_handler]
 pub async fn new_project_handler(
     State(shared_state): State<SharedState>,
     Json(project): Json<ProjectDetails>,
-) -> Result<(), ApiError> {
-    let mut state = shared_state.lock().unwrap();
+) -> Result<Json<JobStatus>, ApiError> {
+    // Parsing the input files can take minutes. The project is built in a
+    // background job that the frontend polls through /jobs/{job_id}.
+    let job = jobs::spawn_create(shared_state, project)?;
+    tracing::info!("Started job {} creating project", job.job_id);
 
-    tracing::info!("Creating project: {}", project.project_id.clone());
-    state
-        .add_project(project)
-        .inspect_err(|err| tracing::error!("Failed: {:?}", err))?;
-    tracing::info!("Project created successfully");
+    Ok(Json(job))
+}
 
-    Ok(())
+/// Check the inputs of a project without creating it.
+#[debug_handler]
+pub async fn preflight_project(
+    State(shared_state): State<SharedState>,
+    Json(project): Json<ProjectDetails>,
+) -> Result<Json<PreflightReport>, ApiError> {
+    let mut project = project;
+    project.output_path = read_lock(&shared_state)?.resolve_path(project.output_path);
+
+    let report = tokio::task::spawn_blocking(move || validation::preflight(&project))
+        .await
+        .map_err(|e| ApiError::Io(e.to_string()))?;
+
+    Ok(Json(report))
 }
 
 // pub async fn get_project_contigs(
@@ -46,13 +78,12 @@ pub async fn get_contigs_in_bin(
     State(shared_state): State<SharedState>,
     Path(path_params): Path<(String, String)>,
 ) -> Result<Json<Vec<String>>, ApiError> {
-    let state = shared_state.lock().unwrap();
-
     let (project_id, bin) = path_params;
 
-    let project = state
+    let project = read_lock(&shared_state)?
         .get_project(&project_id)
         .inspect_err(|err| tracing::error!("Failed to fetch project: {:?}", err))?;
+    let project = read_lock(&project)?;
 
     let filtered = project
         .bins
@@ -70,26 +101,26 @@ pub async fn get_contigs_in_bin(
 pub async fn get_bin_metadata(
     State(shared_state): State<SharedState>,
     Path(project_id): Path<String>,
+    Query(params): Query<BinQueryParams>,
 ) -> Result<Json<Vec<Bin>>, ApiError> {
-    let state = shared_state.lock().unwrap();
-
-    let project = state
+    let project = read_lock(&shared_state)?
         .get_project(&project_id)
         .inspect_err(|err| tracing::error!("Failed to fetch project: {:?}", err))?;
+    let project = read_lock(&project)?;
     tracing::info!("Fetching bin metadata");
 
-    let bins: Vec<Bin> = project.bins.iter().map(|b| b.1.clone()).collect();
+    let filter = params.filter()?;
+    let mut bins: Vec<&Bin> = project.bins.values().filter(|b| filter.keeps(b)).collect();
+    params.sort_by.sort(&mut bins, params.descending);
 
-    Ok(Json(bins))
+    Ok(Json(bins.into_iter().cloned().collect()))
 }
 
 #[debug_handler]
 pub async fn get_projects(
     State(shared_state): State<SharedState>,
 ) -> Result<Json<Vec<String>>, ApiError> {
-    let state = shared_state.lock().unwrap();
-
-    let projects = state.get_all_project_ids();
+    let projects = read_lock(&shared_state)?.get_all_project_ids();
     Ok(Json(projects))
 }
 
@@ -97,14 +128,130 @@ pub async fn get_projects(
 pub async fn load_project(
     State(shared_state): State<SharedState>,
     Json(path): Json<PathBuf>,
+) -> Result<Json<JobStatus>, ApiError> {
+    let job = jobs::spawn_load(shared_state, path)?;
+    tracing::info!("Started job {} loading project", job.job_id);
+
+    Ok(Json(job))
+}
+
+#[debug_handler]
+pub async fn get_loaded_projects(
+    State(shared_state): State<SharedState>,
+) -> Result<Json<Vec<ProjectSummary>>, ApiError> {
+    let handles: Vec<_> = read_lock(&shared_state)?
+        .projects
+        .values()
+        .cloned()
+        .collect();
+
+    let mut summaries = handles
+        .iter()
+        .map(|handle| Ok(read_lock(handle)?.summary()))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+    summaries.sort_by(|a, b| a.project_id.cmp(&b.project_id));
+
+    Ok(Json(summaries))
+}
+
+/// List the project.toml files under the configured projects directory.
+#[debug_handler]
+pub async fn discover_projects(
+    State(shared_state): State<SharedState>,
+) -> Result<Json<Vec<ProjectListing>>, ApiError> {
+    let (root, handles) = {
+        let state = read_lock(&shared_state)?;
+        let root = state.projects_dir.clone().ok_or_else(|| {
+            ApiError::NotFound("Projects directory. Start the server with --projects-dir".into())
+        })?;
+        (root, state.projects.values().cloned().collect::<Vec<_>>())
+    };
+
+    // A project whose lock is poisoned is listed as not loaded rather than
+    // failing the whole listing.
+    let loaded_dirs = handles
+        .iter()
+        .filter_map(|handle| match read_lock(handle) {
+            Ok(project) => Some(project.outdir.clone()),
+            Err(e) => {
+                tracing::warn!("Skipping a loaded project in discovery: {}", e);
+                None
+            }
+        })
+        .collect::<HashSet<_>>();
+
+    let discovered = tokio::task::spawn_blocking(move || discovery::discover_projects(&root))
+        .await
+        .map_err(|e| ApiError::Io(e.to_string()))??;
+
+    let listings = discovered
+        .into_iter()
+        .map(|project| {
+            let loaded = project
+                .details
+                .as_ref()
+                .is_some_and(|d| loaded_dirs.contains(&d.output_path));
+            ProjectListing { project, loaded }
+        })
+        .collect();
+
+    Ok(Json(listings))
+}
+
+/// Remove a project from the server. Unsaved changes are lost unless `save`
+/// is set.
+#[debug_handler]
+pub async fn close_project(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+    Query(options): Query<CloseOptions>,
 ) -> Result<(), ApiError> {
-    let mut state = shared_state.lock().unwrap();
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+
+    // The project stays write locked until it is removed, so a change made
+    // after the save can't be lost. Nothing takes a project lock while
+    // holding the state lock, so this can't deadlock.
+    with_project_mut(project, move |project| {
+        if options.save {
+            project.save_metadata()?;
+        }
+        write_lock(&shared_state)?.remove_project(&project_id)?;
+        tracing::info!("Closed project: {}", project_id);
+        Ok(())
+    })
+    .await
+}
 
-    state
-        .load_project(path)
-        .inspect_err(|err| tracing::error!("Failed: {:?}", err))?;
-    tracing::info!("Project loaded successfully");
-    Ok(())
+#[debug_handler]
+pub async fn get_jobs(
+    State(shared_state): State<SharedState>,
+) -> Result<Json<Vec<JobStatus>>, ApiError> {
+    Ok(Json(read_lock(&shared_state)?.get_all_jobs()))
+}
+
+#[debug_handler]
+pub async fn get_job(
+    State(shared_state): State<SharedState>,
+    Path(job_id): Path<u64>,
+) -> Result<Json<JobStatus>, ApiError> {
+    let state = read_lock(&shared_state)?;
+    Ok(Json(state.get_job(job_id)?.status()))
+}
+
+/// Ask a running job to stop. The job notices between phases and chunks, so
+/// it may still be running when this returns.
+#[debug_handler]
+pub async fn cancel_job(
+    State(shared_state): State<SharedState>,
+    Path(job_id): Path<u64>,
+) -> Result<Json<JobStatus>, ApiError> {
+    let state = read_lock(&shared_state)?;
+    let job = state.get_job(job_id)?;
+    if job.state == JobState::Running {
+        tracing::info!("Cancelling job {}", job_id);
+        job.progress.cancel();
+    }
+    Ok(Json(job.status()))
 }
 
 #[debug_handler]
@@ -113,24 +260,141 @@ pub async fn query_heatmap_data(
     Path(project_id): Path<String>,
     Json(filters): Json<MethDataFilters>,
 ) -> Result<Json<HeatmapData>, ApiError> {
-    let state = shared_state.lock().unwrap();
-    let project = state.get_project(&project_id)?;
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
 
-    let hm_data = project.get_heatmap_data(filters)?;
+    let hm_data = with_project(project, move |project| get_heatmap_data(project, filters)).await?;
 
     Ok(Json(hm_data))
 }
 
+#[debug_handler]
+pub async fn get_contamination_scores(
+    State(shared_state): State<SharedState>,
+    Path(path_params): Path<(String, String)>,
+    Query(options): Query<ScoringOptions>,
+) -> Result<Json<Vec<ContigScore>>, ApiError> {
+    let (project_id, bin) = path_params;
+
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+
+    let scores = with_project(project, move |project| {
+        Ok(project.score_contamination(&bin, &options)?)
+    })
+    .await?;
+
+    Ok(Json(scores))
+}
+
+#[debug_handler]
+pub async fn get_candidate_bins(
+    State(shared_state): State<SharedState>,
+    Path(path_params): Path<(String, String)>,
+    Query(options): Query<ScoringOptions>,
+) -> Result<Json<Vec<BinCandidate>>, ApiError> {
+    let (project_id, contig) = path_params;
+
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+
+    let candidates = with_project(project, move |project| {
+        Ok(project.candidate_bins(&contig, &options)?)
+    })
+    .await?;
+
+    Ok(Json(candidates))
+}
+
+#[debug_handler]
+pub async fn move_contig(
+    State(shared_state): State<SharedState>,
+    Path(path_params): Path<(String, String)>,
+    Json(contig_move): Json<ContigMove>,
+) -> Result<(), ApiError> {
+    let (project_id, contig) = path_params;
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let mut project = write_lock(&project)?;
+
+    project.move_contig(&contig, contig_move)?;
+
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn get_unbinned_contigs(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+) -> Result<Json<Vec<UnbinnedContig>>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let project = read_lock(&project)?;
+
+    Ok(Json(project.unbinned()))
+}
+
+#[debug_handler]
+pub async fn get_recruitment_candidates(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+    Query(options): Query<ScoringOptions>,
+) -> Result<Json<Vec<Recruitment>>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+
+    let recruits = with_project(project, move |project| {
+        Ok(project.recruitment_candidates(&options))
+    })
+    .await?;
+
+    Ok(Json(recruits))
+}
+
+#[debug_handler]
+pub async fn recruit_contig(
+    State(shared_state): State<SharedState>,
+    Path((project_id, contig)): Path<(String, String)>,
+    Json(recruit): Json<ContigRecruit>,
+) -> Result<(), ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let mut project = write_lock(&project)?;
+
+    project.recruit_contig(&contig, recruit)?;
+
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn get_conflicts(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+) -> Result<Json<Vec<ContigConflict>>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let project = read_lock(&project)?;
+
+    Ok(Json(project.conflicts()))
+}
+
+#[debug_handler]
+pub async fn resolve_conflict(
+    State(shared_state): State<SharedState>,
+    Path((project_id, contig)): Path<(String, String)>,
+    Json(resolution): Json<ConflictResolution>,
+) -> Result<(), ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let mut project = write_lock(&project)?;
+
+    project.resolve_conflict(&contig, resolution)?;
+
+    Ok(())
+}
+
 pub async fn update_contig_metadata(
     State(shared_state): State<SharedState>,
     Path(project_id): Path<String>,
     Json(metadata): Json<MetadataUpdate>,
 ) -> Result<(), ApiError> {
-    let mut state = shared_state.lock().unwrap();
-
-    let project = state.get_mut_project(&project_id).inspect_err(|e| {
-        tracing::error!("Error finding project'{}': {}", project_id, e.to_string())
-    })?;
+    let project = read_lock(&shared_state)?
+        .get_project(&project_id)
+        .inspect_err(|e| {
+            tracing::error!("Error finding project'{}': {}", project_id, e.to_string())
+        })?;
+    let mut project = write_lock(&project)?;
 
     project.update_metadata(metadata)?;
 
@@ -141,11 +405,99 @@ pub async fn save_contig_metadata(
     State(shared_state): State<SharedState>,
     Json(project_id): Json<String>,
 ) -> Result<Json<String>, ApiError> {
-    let state = shared_state.lock().unwrap();
-
-    let project = state.get_project(&project_id)?;
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
 
-    project.save_metadata()?;
+    // Saves rewrite the store and the TSV, so they must not overlap.
+    with_project_mut(project, |project| Ok(project.save_metadata()?)).await?;
 
     Ok(Json("Metadata saved successfully".to_string()))
 }
+
+#[debug_handler]
+pub async fn export_bins_fasta(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+    Json(request): Json<FastaExportRequest>,
+) -> Result<Json<Vec<BinFastaExport>>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+
+    let exported = with_project(project, move |project| {
+        let output_dir = export_path(&project.outdir, request.output_dir)?;
+        Ok(project.export_fasta(output_dir, request.ambiguous)?)
+    })
+    .await
+    .inspect_err(|err| tracing::error!("Failed: {:?}", err))?;
+
+    Ok(Json(exported))
+}
+
+#[debug_handler]
+pub async fn export_contig_bin(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+    Json(request): Json<ContigBinExportRequest>,
+) -> Result<Json<ContigBinExport>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+
+    let exported = with_project(project, move |project| {
+        let output_path = export_path(&project.outdir, request.output_path)?;
+        Ok(project.export_contig_bin(output_path, request.ambiguous)?)
+    })
+    .await
+    .inspect_err(|err| tracing::error!("Failed: {:?}", err))?;
+
+    Ok(Json(exported))
+}
+
+#[debug_handler]
+pub async fn get_history(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+) -> Result<Json<Vec<HistoryEvent>>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let project = read_lock(&project)?;
+
+    Ok(Json(project.history.events().to_vec()))
+}
+
+#[debug_handler]
+pub async fn undo_change(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+) -> Result<Json<HistoryEvent>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let mut project = write_lock(&project)?;
+
+    let event = project
+        .undo()
+        .ok_or_else(|| ApiError::NotFound("No change to undo".to_string()))?;
+
+    Ok(Json(event))
+}
+
+#[debug_handler]
+pub async fn redo_change(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+) -> Result<Json<HistoryEvent>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let mut project = write_lock(&project)?;
+
+    let event = project
+        .redo()
+        .ok_or_else(|| ApiError::NotFound("No change to redo".to_string()))?;
+
+    Ok(Json(event))
+}
+
+#[debug_handler]
+pub async fn revert_changes(
+    State(shared_state): State<SharedState>,
+    Path(project_id): Path<String>,
+    Json(request): Json<RevertRequest>,
+) -> Result<Json<Vec<HistoryEvent>>, ApiError> {
+    let project = read_lock(&shared_state)?.get_project(&project_id)?;
+    let mut project = write_lock(&project)?;
+
+    Ok(Json(project.revert_to(request.timestamp)))
+}
@@ -0,0 +1,292 @@
+use std::collections::{HashMap, HashSet};
+
+use epimetheus_methylome::Motif;
+
+use mag_core::{
+    analysis::{coverage::coverage_deviations, taxonomy::labels_agree},
+    bin::BinId,
+    clustering::hierarchical_cluster,
+    contig::{Contig, ContigId},
+    project::Project,
+};
+
+use crate::{
+    errors::ApiError,
+    models::{ContigMetadata, HeatmapData, MethDataFilters},
+};
+
+pub fn get_heatmap_data(
+    project: &Project,
+    filters: MethDataFilters,
+) -> Result<HeatmapData, ApiError> {
+    let contigs_filter: HashSet<&str> = match filters.selection {
+        crate::models::ContigSelection::Bin(ref b) => project
+            .bins
+            .get(&BinId(b.to_string()))
+            .ok_or_else(|| ApiError::Query(format!("Bin '{}' not found.", b)))?
+            .contig_metadata
+            .iter()
+            .map(|c| c.contig_id.0.as_str())
+            .collect(),
+        crate::models::ContigSelection::Contigs(ref c) => c.iter().map(|id| id.as_str()).collect(),
+        crate::models::ContigSelection::Unbinned => project
+            .unbinned_contigs()
+            .into_iter()
+            .map(|c| c.contig_id.0.as_str())
+            .collect(),
+    };
+
+    if filters.has_sequence_filter() && project.assembly_path.is_none() {
+        return Err(ApiError::Query(format!(
+            "Project '{}' has no assembly to filter contigs by length or GC content.",
+            project.id
+        )));
+    }
+
+    let mut contigs = project
+        .contig_methylation
+        .iter()
+        .filter(|(id, contig)| {
+            contigs_filter.contains(id.0.as_str())
+                && filters.keeps_sequence(contig.sequence.as_ref())
+        })
+        .map(|(_id, contig)| contig)
+        .collect::<Vec<&Contig>>();
+    contigs.sort_by(|a, b| a.contig_id.0.cmp(&b.contig_id.0));
+
+    // Iter through hashset will provide a random access each time. Therefore
+    // we collect to vector first and sort it!
+    let mut motif_vec: Vec<_> = project.motifs.iter().collect();
+    motif_vec.sort_by_cached_key(|m| motif_label(m));
+
+    let mut contig_meth_matrix = Vec::new();
+    for contig in &contigs {
+        let mut meth_values = Vec::new();
+        for motif in &motif_vec {
+            let motif_signature = contig.motifs.get(*motif);
+            let val = motif_signature.and_then(|m| {
+                if filters
+                    .min_n_motif_obs
+                    .is_some_and(|f| m.n_motif_obs < f as u32)
+                    || filters.min_coverage.is_some_and(|f| m.mean_coverage < f)
+                {
+                    None
+                } else {
+                    Some(m.methylation_value)
+                }
+            });
+            meth_values.push(val);
+        }
+        contig_meth_matrix.push(meth_values);
+    }
+
+    if let Some(f) = filters.min_methylation_value {
+        let mut motifs_passing_filter = Vec::new();
+        for i in 0..motif_vec.len() {
+            let max_meth_value = contig_meth_matrix
+                .iter()
+                .filter_map(|row| row[i])
+                .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+            if max_meth_value.is_some_and(|max_val| max_val >= f) {
+                motifs_passing_filter.push(i);
+            }
+        }
+
+        motif_vec = motifs_passing_filter
+            .iter()
+            .map(|idx| motif_vec[*idx])
+            .collect();
+        contig_meth_matrix = contig_meth_matrix
+            .iter()
+            .map(|row| motifs_passing_filter.iter().map(|&i| row[i]).collect())
+            .collect()
+    };
+
+    if let Some(f) = filters.min_motif_variance {
+        let mut retained_motif_idxs = Vec::new();
+        for motif_idx in 0..motif_vec.len() {
+            let values = contig_meth_matrix
+                .iter()
+                .filter_map(|row| row[motif_idx])
+                .collect::<Vec<f64>>();
+
+            // The sample variance needs two values. Motifs with fewer in the
+            // selection can't pass a variance filter.
+            let n = values.len();
+            if n < 2 {
+                continue;
+            }
+            let mean = values.iter().sum::<f64>() / n as f64;
+
+            let sum_square = values.iter().map(|v| (v - mean).powf(2.0)).sum::<f64>();
+            let var = sum_square / ((n - 1) as f64);
+
+            if var < f {
+                continue;
+            }
+
+            retained_motif_idxs.push(motif_idx);
+        }
+        let filtered_motifs: Vec<_> = retained_motif_idxs
+            .iter()
+            .map(|&idx| motif_vec[idx])
+            .collect();
+
+        contig_meth_matrix = contig_meth_matrix
+            .into_iter()
+            .map(|row| retained_motif_idxs.iter().map(|&idx| row[idx]).collect())
+            .collect();
+
+        motif_vec = filtered_motifs;
+    }
+
+    let mut contig_ids: Vec<String> = contigs.iter().map(|c| c.contig_id.0.clone()).collect();
+    let mut motif_labels: Vec<String> = motif_vec.iter().map(|m| motif_label(m)).collect();
+
+    let (contig_dendrogram, motif_dendrogram) = match filters.clustering {
+        Some(options) => {
+            let contig_dendrogram =
+                hierarchical_cluster(&contig_meth_matrix, options.metric, options.linkage);
+            let contig_order = contig_dendrogram.leaf_order();
+
+            let motif_columns = (0..motif_labels.len())
+                .map(|i| contig_meth_matrix.iter().map(|row| row[i]).collect())
+                .collect::<Vec<Vec<Option<f64>>>>();
+            let motif_dendrogram =
+                hierarchical_cluster(&motif_columns, options.metric, options.linkage);
+            let motif_order = motif_dendrogram.leaf_order();
+
+            contig_ids = contig_order
+                .iter()
+                .map(|&i| contig_ids[i].clone())
+                .collect();
+            motif_labels = motif_order
+                .iter()
+                .map(|&i| motif_labels[i].clone())
+                .collect();
+            contig_meth_matrix = contig_order
+                .iter()
+                .map(|&r| {
+                    motif_order
+                        .iter()
+                        .map(|&c| contig_meth_matrix[r][c])
+                        .collect()
+                })
+                .collect();
+
+            (
+                Some(contig_dendrogram.relabel_leaves(&contig_order)),
+                Some(motif_dendrogram.relabel_leaves(&motif_order)),
+            )
+        }
+        None => (None, None),
+    };
+
+    let bin_taxonomy = match filters.selection {
+        crate::models::ContigSelection::Bin(ref b) => project
+            .bins
+            .get(&BinId(b.to_string()))
+            .and_then(|bin| bin.taxonomy.clone()),
+        _ => None,
+    };
+
+    let metadata = match filters.selection {
+        crate::models::ContigSelection::Bin(b) => {
+            let bin = project.bins.get(&BinId(b)).unwrap();
+            let conflicts: HashMap<ContigId, Vec<BinId>> = project
+                .conflicts()
+                .into_iter()
+                .map(|c| (c.contig_id, c.bins))
+                .collect();
+            let coverage = coverage_deviations(
+                bin.contig_metadata
+                    .iter()
+                    .filter_map(|c| project.contig_methylation.get(&c.contig_id)),
+            );
+            let contig_metadata: HashMap<String, ContigMetadata> = bin
+                .contig_metadata
+                .iter()
+                .map(|c| {
+                    let contig = project.contig_methylation.get(&c.contig_id);
+                    let cm = ContigMetadata {
+                        contig_id: c.contig_id.0.clone(),
+                        assignment: c.assignment,
+                        mean_coverage: contig.map(|c| c.mean_coverage).unwrap_or(0.0),
+                        note: c.note.clone(),
+                        tags: c.tags.clone(),
+                        other_bins: conflicts
+                            .get(&c.contig_id)
+                            .into_iter()
+                            .flatten()
+                            .filter(|other| *other != &bin.id)
+                            .map(|other| other.0.clone())
+                            .collect(),
+                        sequence: contig.and_then(|c| c.sequence),
+                        sample_depths: contig.and_then(|c| c.sample_depths.clone()),
+                        coverage_deviation: coverage.get(c.contig_id.0.as_str()).copied(),
+                        taxonomy: contig.and_then(|c| c.taxonomy.clone()),
+                        taxonomy_agrees: contig
+                            .and_then(|c| c.taxonomy.as_deref())
+                            .zip(bin.taxonomy.as_deref())
+                            .and_then(|(contig, bin)| labels_agree(contig, bin)),
+                    };
+                    (c.contig_id.0.clone(), cm)
+                })
+                .collect();
+
+            Some(contig_metadata)
+        }
+        selection => {
+            let requested = match selection {
+                crate::models::ContigSelection::Contigs(c) => c,
+                _ => contig_ids.clone(),
+            };
+            let contig_metadata = requested
+                .into_iter()
+                .map(|c| {
+                    let contig_id = ContigId(c.clone());
+                    let contig = project.contig_methylation.get(&contig_id);
+                    let cm = ContigMetadata {
+                        contig_id: contig_id.0.clone(),
+                        assignment: mag_core::contig::Assignment::None,
+                        mean_coverage: contig.map(|c| c.mean_coverage).unwrap_or(1.0),
+                        note: None,
+                        tags: Vec::new(),
+                        other_bins: Vec::new(),
+                        sequence: contig.and_then(|c| c.sequence),
+                        sample_depths: contig.and_then(|c| c.sample_depths.clone()),
+                        coverage_deviation: None,
+                        taxonomy: contig.and_then(|c| c.taxonomy.clone()),
+                        taxonomy_agrees: None,
+                    };
+                    (c, cm)
+                })
+                .collect::<HashMap<String, ContigMetadata>>();
+
+            Some(contig_metadata)
+        }
+    };
+
+    let hm = HeatmapData {
+        contigs: contig_ids,
+        motifs: motif_labels,
+        matrix: contig_meth_matrix,
+        metadata,
+        depth_samples: project.depth_samples.clone(),
+        bin_taxonomy,
+        contig_dendrogram,
+        motif_dendrogram,
+    };
+
+    Ok(hm)
+}
+
+fn motif_label(motif: &Motif) -> String {
+    format!(
+        "{}_{}_{}",
+        motif.sequence_to_string(),
+        motif.mod_type.to_pileup_code(),
+        motif.mod_position
+    )
+}
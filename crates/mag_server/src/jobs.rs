@@ -0,0 +1,189 @@
+use std::{path::PathBuf, sync::Arc};
+
+use mag_core::{
+    errors::DataError,
+    io::loader::{LoadProgress, LoadProgressSnapshot},
+    project::Project,
+};
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::{
+    SharedState,
+    errors::ApiError,
+    models::{ProjectDetails, read_lock, write_lock},
+};
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum JobKind {
+    Create,
+    Load,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, TS, PartialEq, Eq)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub enum JobState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Project creation or loading running on a blocking thread.
+pub struct Job {
+    pub id: u64,
+    pub kind: JobKind,
+    pub project_id: Option<String>,
+    pub progress: Arc<LoadProgress>,
+    pub state: JobState,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct JobStatus {
+    #[ts(type = "number")]
+    pub job_id: u64,
+    pub kind: JobKind,
+    pub project_id: Option<String>,
+    pub state: JobState,
+    pub progress: LoadProgressSnapshot,
+    pub error: Option<String>,
+}
+
+impl Job {
+    pub fn status(&self) -> JobStatus {
+        JobStatus {
+            job_id: self.id,
+            kind: self.kind.clone(),
+            project_id: self.project_id.clone(),
+            state: self.state,
+            progress: self.progress.snapshot(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+enum JobInput {
+    Create(Box<ProjectDetails>),
+    Load(PathBuf),
+}
+
+/// Register a job for creating `details` and start it.
+pub fn spawn_create(state: SharedState, details: ProjectDetails) -> Result<JobStatus, ApiError> {
+    let details = read_lock(&state)?.prepare_project(details)?;
+    let project_id = Some(details.project_id.clone());
+    spawn(
+        state,
+        JobKind::Create,
+        project_id,
+        JobInput::Create(Box::new(details)),
+    )
+}
+
+/// Register a job for loading the project.toml at `path` and start it.
+pub fn spawn_load(state: SharedState, path: PathBuf) -> Result<JobStatus, ApiError> {
+    let path = read_lock(&state)?.resolve_path(path);
+    spawn(state, JobKind::Load, None, JobInput::Load(path))
+}
+
+fn spawn(
+    state: SharedState,
+    kind: JobKind,
+    project_id: Option<String>,
+    input: JobInput,
+) -> Result<JobStatus, ApiError> {
+    let progress = Arc::new(LoadProgress::default());
+    let (job_id, status) = {
+        let mut guard = write_lock(&state)?;
+        let job_id = guard.next_job_id();
+        let job = Job {
+            id: job_id,
+            kind,
+            project_id,
+            progress: progress.clone(),
+            state: JobState::Running,
+            error: None,
+        };
+        let status = job.status();
+        guard.jobs.insert(job_id, job);
+        guard.prune_jobs();
+        (job_id, status)
+    };
+
+    let handle = tokio::task::spawn_blocking(move || match input {
+        JobInput::Create(details) => {
+            tracing::info!("Creating project: {}", details.project_id);
+            Project::new_with_progress(*details, &progress)
+        }
+        JobInput::Load(path) => {
+            tracing::info!("Loading project from: {}", path.display());
+            Project::load_from_path_with_progress(path, &progress)
+        }
+    });
+    // A panicking reader must still end the job, or it stays running forever.
+    tokio::spawn(async move {
+        match handle.await {
+            Ok(result) => finish(&state, job_id, result),
+            Err(e) => {
+                tracing::error!("Job {} panicked: {}", job_id, e);
+                set_state(
+                    &state,
+                    job_id,
+                    JobState::Failed,
+                    Some("The job stopped unexpectedly".to_string()),
+                );
+            }
+        }
+    });
+
+    Ok(status)
+}
+
+fn finish(state: &SharedState, job_id: u64, result: Result<Project, DataError>) {
+    let Ok(mut guard) = write_lock(state) else {
+        tracing::error!("Job {} finished but the server state is poisoned", job_id);
+        return;
+    };
+
+    let (job_state, error) = match result {
+        Ok(project) => {
+            let project_id = project.id.clone();
+            match guard.insert_project(project) {
+                Ok(()) => {
+                    tracing::info!("Job {}: project '{}' ready", job_id, project_id);
+                    if let Some(job) = guard.jobs.get_mut(&job_id) {
+                        job.project_id = Some(project_id);
+                    }
+                    (JobState::Completed, None)
+                }
+                Err(e) => (JobState::Failed, Some(e.to_string())),
+            }
+        }
+        Err(DataError::Cancelled) => {
+            tracing::info!("Job {} cancelled", job_id);
+            (JobState::Cancelled, None)
+        }
+        Err(e) => {
+            tracing::error!("Job {} failed: {}", job_id, e);
+            (JobState::Failed, Some(e.to_string()))
+        }
+    };
+
+    if let Some(job) = guard.jobs.get_mut(&job_id) {
+        job.state = job_state;
+        job.error = error;
+    }
+}
+
+fn set_state(state: &SharedState, job_id: u64, job_state: JobState, error: Option<String>) {
+    let Ok(mut guard) = write_lock(state) else {
+        tracing::error!("Job {} finished but the server state is poisoned", job_id);
+        return;
+    };
+    if let Some(job) = guard.jobs.get_mut(&job_id) {
+        job.state = job_state;
+        job.error = error;
+    }
+}
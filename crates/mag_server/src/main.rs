@@ -1,12 +1,17 @@
+pub mod config;
 pub mod errors;
 pub mod handlers;
 pub mod models;
+pub mod heatmap;
+pub mod jobs;
 pub mod routes;
-pub mod project;
 
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, RwLock};
 
-use crate::{models::AppState, routes::create_api_router};
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+use crate::{config::ServerConfig, models::AppState, routes::create_api_router};
 
 // create a svelte frotend in appropriate place in repo.
 // allow user to create a project
@@ -15,21 +20,41 @@ use crate::{models::AppState, routes::create_api_router};
 // user should be able to select/deselect motifs to see
 // user should be able to mark contigs in a bin which will mark these contigs as contamination in the project file created.
 
-type SharedState = Arc<Mutex<AppState>>;
+type SharedState = Arc<RwLock<AppState>>;
 
 #[tokio::main]
 async fn main() {
+    let config = ServerConfig::parse();
+
+    let filter = match config.log_level {
+        Some(ref level) => EnvFilter::new(level),
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
+    };
     tracing_subscriber::fmt()
         .with_target(false)
+        .with_env_filter(filter)
         .compact()
         .init();
 
-    let state = AppState::new();
-    let shared_state: SharedState = Arc::new(Mutex::new(state));
+    let mut state = AppState::new();
+    state.projects_dir = config.projects_dir.clone();
+
+    for path in &config.load {
+        match state.load_project(path.clone()) {
+            Ok(()) => tracing::info!("Loaded project: {}", path.display()),
+            Err(e) => tracing::error!("Could not load '{}': {}", path.display(), e),
+        }
+    }
+
+    let shared_state: SharedState = Arc::new(RwLock::new(state));
 
     // let app = Router::new().route("/", get(root)).with_state(shared_state);
     let app = create_api_router(shared_state);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    let address = config.bind_address();
+    let listener = tokio::net::TcpListener::bind(&address)
+        .await
+        .unwrap_or_else(|e| panic!("Could not bind to {}: {}", address, e));
+    tracing::info!("Listening on {}", address);
     axum::serve(listener, app).await.unwrap();
 }
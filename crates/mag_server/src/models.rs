@@ -1,83 +1,208 @@
-use crate::errors::ApiError;
-use crate::project::Project;
+use crate::{
+    errors::ApiError,
+    jobs::{Job, JobState, JobStatus},
+};
 use mag_core::{
-    bin::{BinId, BinQuality},
-    contig::{Assignment, ContigAssignment},
+    bin::{BinFilter, BinQuality, BinSortKey},
+    clustering::{Dendrogram, DistanceMetric, Linkage},
+    contig::{Assignment, SequenceStats},
+    discovery::DiscoveredProject,
+    errors::DataError,
+    export::AmbiguousPolicy,
+    project::Project,
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
 use ts_rs::TS;
 
+pub use mag_core::project::{
+    ConflictResolution, ContigMove, ContigRecruit, MetadataUpdate, ProjectDetails,
+};
+
+/// A loaded project. Every project has its own lock, so a slow query on one
+/// project does not hold up requests on the others.
+pub type ProjectHandle = Arc<RwLock<Project>>;
+
+/// Take a read lock, reporting a poisoned lock as an error instead of
+/// panicking in every later request.
+pub fn read_lock<T>(lock: &RwLock<T>) -> Result<RwLockReadGuard<'_, T>, ApiError> {
+    lock.read().map_err(|_| ApiError::LockPoisoned)
+}
+
+pub fn write_lock<T>(lock: &RwLock<T>) -> Result<RwLockWriteGuard<'_, T>, ApiError> {
+    lock.write().map_err(|_| ApiError::LockPoisoned)
+}
+
+/// Run `f` with the project read locked on the blocking thread pool. Scoring,
+/// clustering and file writes can take a while and must not block a runtime
+/// worker while holding the lock.
+pub async fn with_project<T, F>(project: ProjectHandle, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&Project) -> Result<T, ApiError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&*read_lock(&project)?))
+        .await
+        .map_err(|e| ApiError::Io(e.to_string()))?
+}
+
+/// Like `with_project`, with the project write locked.
+pub async fn with_project_mut<T, F>(project: ProjectHandle, f: F) -> Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut Project) -> Result<T, ApiError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || f(&mut *write_lock(&project)?))
+        .await
+        .map_err(|e| ApiError::Io(e.to_string()))?
+}
+
+/// Finished jobs kept for clients polling their status.
+const MAX_FINISHED_JOBS: usize = 50;
+
 pub struct AppState {
-    pub projects: HashMap<String, Project>,
+    pub projects: HashMap<String, ProjectHandle>,
+    pub projects_dir: Option<PathBuf>,
+    pub jobs: HashMap<u64, Job>,
+    last_job_id: u64,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             projects: HashMap::new(),
+            projects_dir: None,
+            jobs: HashMap::new(),
+            last_job_id: 0,
+        }
+    }
+
+    /// Resolve a relative path against the configured projects directory.
+    pub fn resolve_path(&self, path: PathBuf) -> PathBuf {
+        match self.projects_dir {
+            Some(ref dir) if path.is_relative() => dir.join(path),
+            _ => path,
         }
     }
 
-    pub fn add_project(&mut self, payload: ProjectDetails) -> Result<(), ApiError> {
+    /// Resolve the output path and check that the project id is free. The
+    /// project itself is built without holding the state lock.
+    pub fn prepare_project(&self, mut payload: ProjectDetails) -> Result<ProjectDetails, ApiError> {
+        let creating = self.jobs.values().any(|job| {
+            job.state == JobState::Running
+                && job.project_id.as_deref() == Some(payload.project_id.as_str())
+        });
+        if creating || self.projects.contains_key(&payload.project_id) {
+            return Err(ApiError::ProjectExists(payload.project_id));
+        }
+        payload.output_path = self.resolve_path(payload.output_path);
+
+        Ok(payload)
+    }
+
+    pub fn insert_project(&mut self, project: Project) -> Result<(), ApiError> {
         use std::collections::hash_map::Entry;
 
-        match self.projects.entry(payload.project_id.clone()) {
-            Entry::Occupied(_) => Err(ApiError::ProjectExists(payload.project_id)),
+        match self.projects.entry(project.id.clone()) {
+            Entry::Occupied(_) => Err(ApiError::ProjectExists(project.id)),
             Entry::Vacant(entry) => {
-                let new_project = Project::new(payload)?;
-
-                entry.insert(new_project);
+                entry.insert(Arc::new(RwLock::new(project)));
                 Ok(())
             }
         }
     }
 
     pub fn load_project(&mut self, path: PathBuf) -> Result<(), ApiError> {
-        use std::collections::hash_map::Entry;
-
-        let new_project = Project::load_from_path(path)?;
+        let new_project = Project::load_from_path(self.resolve_path(path))?;
         tracing::info!("Loading project: {}", new_project.id.clone());
 
-        match self.projects.entry(new_project.id.clone()) {
-            Entry::Occupied(_) => Err(ApiError::ProjectExists(new_project.id.clone())),
-            Entry::Vacant(entry) => {
-                entry.insert(new_project);
-                Ok(())
-            }
-        }
+        self.insert_project(new_project)
     }
 
-    pub fn get_mut_project(&mut self, project: &str) -> Result<&mut Project, ApiError> {
-        match self.projects.get_mut(project) {
-            Some(p) => Ok(p),
-            None => Err(ApiError::ProjectNotFound(project.to_string())),
+    pub fn next_job_id(&mut self) -> u64 {
+        self.last_job_id += 1;
+        self.last_job_id
+    }
+
+    /// Drop the oldest finished jobs beyond `MAX_FINISHED_JOBS`. Running
+    /// jobs are always kept.
+    pub fn prune_jobs(&mut self) {
+        let mut finished: Vec<u64> = self
+            .jobs
+            .values()
+            .filter(|job| job.state != JobState::Running)
+            .map(|job| job.id)
+            .collect();
+        if finished.len() <= MAX_FINISHED_JOBS {
+            return;
+        }
+        finished.sort_unstable();
+        for job_id in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+            self.jobs.remove(job_id);
         }
     }
 
-    pub fn get_project(&self, project: &str) -> Result<&Project, ApiError> {
+    pub fn get_job(&self, job_id: u64) -> Result<&Job, ApiError> {
+        self.jobs
+            .get(&job_id)
+            .ok_or_else(|| ApiError::NotFound(format!("Job {}", job_id)))
+    }
+
+    pub fn get_all_jobs(&self) -> Vec<JobStatus> {
+        let mut jobs: Vec<JobStatus> = self.jobs.values().map(Job::status).collect();
+        jobs.sort_by_key(|job| job.job_id);
+        jobs
+    }
+
+    pub fn get_project(&self, project: &str) -> Result<ProjectHandle, ApiError> {
         match self.projects.get(project) {
-            Some(p) => Ok(p),
+            Some(p) => Ok(p.clone()),
             None => Err(ApiError::ProjectNotFound(project.to_string())),
         }
     }
+
+    pub fn remove_project(&mut self, project: &str) -> Result<ProjectHandle, ApiError> {
+        self.projects
+            .remove(project)
+            .ok_or_else(|| ApiError::ProjectNotFound(project.to_string()))
+    }
+
     pub fn get_all_project_ids(&self) -> Vec<String> {
         self.projects
             .keys()
-            .into_iter()
             .map(|c| c.to_string())
             .collect::<Vec<String>>()
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+/// A project found under the projects directory.
+#[derive(Serialize, Debug, TS)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
-pub struct ProjectDetails {
-    pub project_id: String,
-    pub methylation_data_path: PathBuf,
-    pub contig_bin_path: PathBuf,
-    pub bin_quality_path: Option<PathBuf>,
-    pub output_path: PathBuf,
+pub struct ProjectListing {
+    #[serde(flatten)]
+    #[ts(flatten)]
+    pub project: DiscoveredProject,
+    /// The project's output directory belongs to a loaded project.
+    pub loaded: bool,
+}
+
+#[derive(Deserialize, Debug, TS)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct CloseOptions {
+    /// Save the curation before closing.
+    #[serde(default)]
+    pub save: bool,
 }
 
 #[derive(Deserialize, Debug, TS)]
@@ -91,6 +216,8 @@ pub struct ProjectFilter {
 pub enum ContigSelection {
     Bin(String),
     Contigs(Vec<String>),
+    /// Contigs with methylation data that are not in any bin.
+    Unbinned,
 }
 
 #[derive(Deserialize, Debug, TS)]
@@ -101,6 +228,41 @@ pub struct MethDataFilters {
     pub min_motif_variance: Option<f64>,
     pub min_coverage: Option<f64>,
     pub min_methylation_value: Option<f64>,
+    /// Sequence filters, only allowed if the project has an assembly.
+    #[ts(type = "number | null")]
+    pub min_length: Option<u64>,
+    pub min_gc_content: Option<f64>,
+    pub max_gc_content: Option<f64>,
+    pub clustering: Option<ClusteringOptions>,
+}
+
+impl MethDataFilters {
+    pub fn has_sequence_filter(&self) -> bool {
+        self.min_length.is_some() || self.min_gc_content.is_some() || self.max_gc_content.is_some()
+    }
+
+    /// Check a contig against the sequence filters. Contigs missing from the
+    /// assembly only pass if no sequence filter is set.
+    pub fn keeps_sequence(&self, sequence: Option<&SequenceStats>) -> bool {
+        if !self.has_sequence_filter() {
+            return true;
+        }
+        let Some(s) = sequence else {
+            return false;
+        };
+        self.min_length.is_none_or(|f| s.length >= f)
+            && self.min_gc_content.is_none_or(|f| s.gc_content >= f)
+            && self.max_gc_content.is_none_or(|f| s.gc_content <= f)
+    }
+}
+
+#[derive(Deserialize, Debug, TS, Clone, Copy)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ClusteringOptions {
+    #[serde(default)]
+    pub linkage: Linkage,
+    #[serde(default)]
+    pub metric: DistanceMetric,
 }
 
 #[derive(Serialize, Debug, TS)]
@@ -110,6 +272,12 @@ pub struct HeatmapData {
     pub motifs: Vec<String>,
     pub matrix: Vec<Vec<Option<f64>>>,
     pub metadata: Option<HashMap<String, ContigMetadata>>,
+    /// Samples of `ContigMetadata::sample_depths`.
+    pub depth_samples: Vec<String>,
+    /// Taxonomy of the selected bin.
+    pub bin_taxonomy: Option<String>,
+    pub contig_dendrogram: Option<Dendrogram>,
+    pub motif_dendrogram: Option<Dendrogram>,
 }
 
 #[derive(Serialize, Deserialize, Debug, TS, Clone)]
@@ -119,17 +287,103 @@ pub struct ContigMetadata {
     pub assignment: Assignment,
     pub mean_coverage: f64,
     pub note: Option<String>,
+    pub tags: Vec<String>,
+    /// Other bins the contig is also assigned to.
+    pub other_bins: Vec<String>,
+    pub sequence: Option<SequenceStats>,
+    pub sample_depths: Option<Vec<f64>>,
+    /// Log2 deviation of the depth profile from the rest of the bin. Only
+    /// set when a bin is selected.
+    pub coverage_deviation: Option<f64>,
+    pub taxonomy: Option<String>,
+    /// Whether `taxonomy` matches the taxonomy of the bin. Only set when a
+    /// bin is selected and both are labelled.
+    pub taxonomy_agrees: Option<bool>,
 }
 
+/// Query of `/projects/{project_id}/bins`. The filter fields are spelled out
+/// rather than flattened from `BinFilter` because query strings can't carry
+/// numbers through a flattened struct, nor lists.
 #[derive(Deserialize)]
 pub struct BinQueryParams {
+    /// Comma separated quality tiers, e.g. `HQ,NC`.
+    pub quality_filter: Option<String>,
+    #[serde(default)]
+    pub sort_by: BinSortKey,
     #[serde(default)]
-    pub quality_filter: Vec<BinQuality>,
+    pub descending: bool,
+    pub min_completeness: Option<f64>,
+    pub max_contamination: Option<f64>,
+    pub min_gc_content: Option<f64>,
+    pub max_gc_content: Option<f64>,
+    pub min_genome_size: Option<u64>,
+    pub min_n50: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, TS, Clone)]
+impl BinQueryParams {
+    pub fn filter(&self) -> Result<BinFilter, ApiError> {
+        let qualities = self
+            .quality_filter
+            .iter()
+            .flat_map(|q| q.split(','))
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(BinQuality::from_str)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ApiError::Query)?;
+
+        Ok(BinFilter {
+            qualities,
+            min_completeness: self.min_completeness,
+            max_contamination: self.max_contamination,
+            min_gc_content: self.min_gc_content,
+            max_gc_content: self.max_gc_content,
+            min_genome_size: self.min_genome_size,
+            min_n50: self.min_n50,
+        })
+    }
+}
+
+#[derive(Deserialize, Debug, TS, Clone, Default)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct FastaExportRequest {
+    #[serde(default)]
+    pub ambiguous: AmbiguousPolicy,
+    /// Relative to the project output directory, which it can't leave.
+    pub output_dir: Option<PathBuf>,
+}
+
+/// Resolve an export path sent by a client against the project output
+/// directory. Paths leaving the directory are refused, so a request can't
+/// write anywhere else the server user can.
+pub fn export_path(outdir: &Path, path: Option<PathBuf>) -> Result<Option<PathBuf>, ApiError> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let resolved = outdir.join(&path);
+    if !resolved.starts_with(outdir) || path.components().any(|c| c == Component::ParentDir) {
+        return Err(ApiError::from(DataError::DataAssertion(format!(
+            "Export path '{}' is outside the project directory '{}'",
+            path.display(),
+            outdir.display()
+        ))));
+    }
+    Ok(Some(resolved))
+}
+
+#[derive(Deserialize, Debug, TS, Clone, Default)]
+#[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
+pub struct ContigBinExportRequest {
+    #[serde(default)]
+    pub ambiguous: AmbiguousPolicy,
+    /// Relative to the project output directory, which it can't leave.
+    pub output_path: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, TS, Clone)]
 #[ts(export, export_to = "../../../contam-map-frontend/src/bindings/")]
-pub struct MetadataUpdate {
-    pub bin: BinId,
-    pub contigs: Vec<ContigAssignment>,
+pub struct RevertRequest {
+    /// Milliseconds since the unix epoch.
+    #[ts(type = "number")]
+    pub timestamp: u64,
 }
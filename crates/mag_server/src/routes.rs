@@ -6,8 +6,12 @@ use axum::{
 use crate::{
     SharedState,
     handlers::{
-        get_bin_metadata, get_contigs_in_bin, get_projects,
-        load_project, new_project_handler, query_heatmap_data, save_contig_metadata,
+        cancel_job, close_project, discover_projects, export_bins_fasta, export_contig_bin,
+        get_bin_metadata, get_candidate_bins, get_conflicts, get_contamination_scores,
+        get_contigs_in_bin, get_history, get_job, get_jobs, get_loaded_projects, get_projects,
+        get_recruitment_candidates, get_unbinned_contigs, load_project, move_contig,
+        new_project_handler, preflight_project, query_heatmap_data, recruit_contig, redo_change,
+        resolve_conflict, revert_changes, save_contig_metadata, undo_change,
         update_contig_metadata,
     },
 };
@@ -16,24 +20,70 @@ pub fn create_api_router(state: SharedState) -> Router {
     let api_routes = Router::new()
         .route("/projects", get(get_projects))
         .route("/projects/create", post(new_project_handler))
+        .route("/projects/preflight", post(preflight_project))
         .route("/projects/load", post(load_project))
+        .route("/projects/loaded", get(get_loaded_projects))
+        .route("/projects/discover", get(discover_projects))
+        .route("/projects/{project_id}/close", post(close_project))
+        .route("/jobs", get(get_jobs))
+        .route("/jobs/{job_id}", get(get_job))
+        .route("/jobs/{job_id}/cancel", post(cancel_job))
         .route("/projects/{project_id}/bins", get(get_bin_metadata))
+        .route(
+            "/projects/{project_id}/bins/{bin}/scores",
+            get(get_contamination_scores),
+        )
         // .route("/projects/{project_id}/contigs", get(get_project_contigs))
         .route(
             "/projects/{project_id}/contigs/{bin}",
             get(get_contigs_in_bin),
         )
+        .route(
+            "/projects/{project_id}/contigs/{contig}/candidates",
+            get(get_candidate_bins),
+        )
+        .route(
+            "/projects/{project_id}/contigs/{contig}/move",
+            post(move_contig),
+        )
+        .route(
+            "/projects/{project_id}/contigs/{contig}/resolve",
+            post(resolve_conflict),
+        )
+        .route("/projects/{project_id}/conflicts", get(get_conflicts))
+        .route("/projects/{project_id}/unbinned", get(get_unbinned_contigs))
+        .route(
+            "/projects/{project_id}/unbinned/candidates",
+            get(get_recruitment_candidates),
+        )
+        .route(
+            "/projects/{project_id}/unbinned/{contig}/recruit",
+            post(recruit_contig),
+        )
         .route(
             "/projects/{project_id}/data/heatmap",
             post(query_heatmap_data),
         )
         .route("/projects/save", post(save_contig_metadata))
+        .route(
+            "/projects/{project_id}/export/fasta",
+            post(export_bins_fasta),
+        )
+        .route(
+            "/projects/{project_id}/export/contig_bin",
+            post(export_contig_bin),
+        )
         .route(
             "/projects/{project_id}/data/update",
             post(update_contig_metadata),
+        )
+        .route("/projects/{project_id}/history", get(get_history))
+        .route("/projects/{project_id}/history/undo", post(undo_change))
+        .route("/projects/{project_id}/history/redo", post(redo_change))
+        .route(
+            "/projects/{project_id}/history/revert",
+            post(revert_changes),
         );
 
-    let router = Router::new().nest("/api", api_routes).with_state(state);
-
-    router
+    Router::new().nest("/api", api_routes).with_state(state)
 }